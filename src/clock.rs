@@ -0,0 +1,120 @@
+use std::time::{Duration, Instant};
+
+/// Most steps run in one go after a stall, the rest of the backlog is dropped.
+const MAX_CATCH_UP_STEPS: u32 = 4;
+
+/// Virtual time for the deterministic mode. The movie only ever advances in
+/// whole `step`s, the wall clock just decides how many are due.
+pub struct FixedClock {
+    step: Duration,
+    origin: Instant,
+    steps: u32,
+    last_wall: Instant,
+    pending: Duration,
+}
+
+impl FixedClock {
+    pub fn new(step: Duration, now: Instant) -> Self {
+        Self {
+            step: step.max(Duration::from_millis(1)),
+            origin: now,
+            steps: 0,
+            last_wall: now,
+            pending: Duration::ZERO,
+        }
+    }
+
+    pub fn step_ms(&self) -> f64 {
+        self.step.as_secs_f64() * 1000.0
+    }
+
+    /// How many steps to run now, `wall_now` being the real time and `speed`
+    /// how much faster than it the movie runs.
+    pub fn due_steps(&mut self, wall_now: Instant, speed: f64) -> u32 {
+        self.pending += wall_now.saturating_duration_since(self.last_wall).mul_f64(speed);
+        self.last_wall = wall_now;
+        let due = (self.pending.as_nanos() / self.step.as_nanos()) as u32;
+        let max_steps = MAX_CATCH_UP_STEPS * speed.ceil() as u32;
+        if due > max_steps {
+            self.pending = Duration::ZERO;
+            max_steps
+        } else {
+            self.pending -= self.step * due;
+            due
+        }
+    }
+
+    /// Marks one step as run.
+    pub fn advance(&mut self) {
+        self.steps += 1;
+    }
+
+    /// The virtual instant the steps run so far have reached.
+    pub fn now(&self) -> Instant {
+        self.origin + self.step * self.steps
+    }
+
+    /// The start time that makes `wall_now` read as the virtual time elapsed so far,
+    /// rounded down to whole milliseconds with half a millisecond to spare, so
+    /// millisecond timers read the same value for the rest of a short step.
+    pub fn start_time(&self, wall_now: Instant) -> Instant {
+        let elapsed = self.step * self.steps;
+        let elapsed = Duration::from_millis(elapsed.as_millis() as u64) + Duration::from_micros(500);
+        wall_now.checked_sub(elapsed).unwrap_or(wall_now)
+    }
+
+    /// Skips the time spent suspended, so resuming does not trigger a catch up.
+    pub fn resume(&mut self, wall_now: Instant) {
+        self.last_wall = wall_now;
+        self.pending = Duration::ZERO;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_follow_the_wall_clock() {
+        let start = Instant::now();
+        let mut clock = FixedClock::new(Duration::from_millis(40), start);
+        assert_eq!(clock.due_steps(start + Duration::from_millis(30), 1.0), 0);
+        assert_eq!(clock.due_steps(start + Duration::from_millis(50), 1.0), 1);
+        assert_eq!(clock.due_steps(start + Duration::from_millis(120), 1.0), 2);
+        clock.advance();
+        clock.advance();
+        assert_eq!(clock.now(), start + Duration::from_millis(80));
+    }
+
+    #[test]
+    fn start_time_pins_elapsed_millis() {
+        let start = Instant::now();
+        let mut clock = FixedClock::new(Duration::from_micros(33_333), start);
+        for _ in 0..3 {
+            clock.advance();
+        }
+        let wall_now = start + Duration::from_secs(5);
+        let elapsed = |at: Instant| at.duration_since(clock.start_time(wall_now)).as_millis();
+        assert_eq!(elapsed(wall_now), 99);
+        assert_eq!(elapsed(wall_now + Duration::from_micros(400)), 99);
+    }
+
+    #[test]
+    fn stalls_do_not_cause_bursts() {
+        let start = Instant::now();
+        let mut clock = FixedClock::new(Duration::from_millis(40), start);
+        assert_eq!(clock.due_steps(start + Duration::from_secs(2), 1.0), MAX_CATCH_UP_STEPS);
+        assert_eq!(clock.due_steps(start + Duration::from_millis(2030), 1.0), 0);
+        clock.resume(start + Duration::from_secs(10));
+        assert_eq!(clock.due_steps(start + Duration::from_millis(10_040), 1.0), 1);
+    }
+
+    #[test]
+    fn speed_scales_due_steps() {
+        let start = Instant::now();
+        let mut clock = FixedClock::new(Duration::from_millis(40), start);
+        assert_eq!(clock.due_steps(start + Duration::from_millis(40), 4.0), 4);
+        assert_eq!(clock.due_steps(start + Duration::from_millis(120), 0.25), 0);
+        assert_eq!(clock.due_steps(start + Duration::from_millis(200), 0.25), 1);
+    }
+}
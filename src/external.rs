@@ -0,0 +1,256 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Mutex,
+};
+
+use jni::{
+    JNIEnv,
+    errors::{Error as JniError, Result as JniResult},
+    objects::{JObject, JObjectArray, JString, JValue},
+    sys::{jint, jsize},
+};
+use log::{error, warn};
+use once_cell::sync::Lazy;
+use ruffle_core::{
+    context::UpdateContext,
+    external::{ExternalInterfaceProvider, Value as ExternalValue},
+};
+
+use crate::ui::JavaHost;
+
+/// Names the host answers `ExternalInterface.call` for.
+static HOST_CALLBACKS: Lazy<Mutex<HashSet<String>>> = Lazy::new(Default::default);
+
+pub fn set_host_callback(name: String, registered: bool) {
+    let mut callbacks = HOST_CALLBACKS.lock().unwrap();
+    if registered {
+        callbacks.insert(name);
+    } else {
+        callbacks.remove(&name);
+    }
+}
+
+/// Hands `value` to the host as `Boolean`, `Double`, `String`, `List` or `Map`, and
+/// `null` for both `null` and `undefined`.
+pub fn to_java<'local>(env: &mut JNIEnv<'local>, value: &ExternalValue) -> JniResult<JObject<'local>> {
+    match value {
+        ExternalValue::Undefined | ExternalValue::Null => Ok(JObject::null()),
+        ExternalValue::Bool(value) => env
+            .call_static_method("java/lang/Boolean", "valueOf", "(Z)Ljava/lang/Boolean;", &[JValue::from(*value)])?
+            .l(),
+        ExternalValue::Number(value) => env
+            .call_static_method("java/lang/Double", "valueOf", "(D)Ljava/lang/Double;", &[JValue::from(*value)])?
+            .l(),
+        ExternalValue::String(value) => Ok(env.new_string(value)?.into()),
+        ExternalValue::List(values) => {
+            let list = env.new_object("java/util/ArrayList", "(I)V", &[JValue::from(values.len() as jint)])?;
+            for value in values {
+                let item = to_java(env, value)?;
+                env.call_method(&list, "add", "(Ljava/lang/Object;)Z", &[JValue::Object(&item)])?;
+                env.delete_local_ref(item)?;
+            }
+            Ok(list)
+        }
+        ExternalValue::Object(values) => {
+            let map = env.new_object("java/util/LinkedHashMap", "()V", &[])?;
+            for (key, value) in values {
+                let key = env.new_string(key)?;
+                let item = to_java(env, value)?;
+                env.call_method(
+                    &map,
+                    "put",
+                    "(Ljava/lang/Object;Ljava/lang/Object;)Ljava/lang/Object;",
+                    &[JValue::Object(&key), JValue::Object(&item)],
+                )?
+                .l()?;
+                env.delete_local_ref(key)?;
+                env.delete_local_ref(item)?;
+            }
+            Ok(map)
+        }
+    }
+}
+
+/// Reads a value from the host, the reverse of `to_java`. Any `Number` reads as a double and
+/// map keys are turned into strings. Other classes throw an `IllegalArgumentException`.
+pub fn from_java(env: &mut JNIEnv, value: &JObject) -> JniResult<ExternalValue> {
+    if value.is_null() {
+        return Ok(ExternalValue::Null);
+    }
+    if env.is_instance_of(value, "java/lang/Boolean")? {
+        return Ok(ExternalValue::Bool(env.call_method(value, "booleanValue", "()Z", &[])?.z()?));
+    }
+    if env.is_instance_of(value, "java/lang/Number")? {
+        return Ok(ExternalValue::Number(env.call_method(value, "doubleValue", "()D", &[])?.d()?));
+    }
+    if env.is_instance_of(value, "java/lang/String")? {
+        return Ok(ExternalValue::String(env.get_string(<&JString>::from(value))?.into()));
+    }
+    if env.is_instance_of(value, "java/util/List")? {
+        let len = env.call_method(value, "size", "()I", &[])?.i()?;
+        let mut values = Vec::with_capacity(len.max(0) as usize);
+        for i in 0..len {
+            let item = env.call_method(value, "get", "(I)Ljava/lang/Object;", &[JValue::from(i)])?.l()?;
+            values.push(from_java(env, &item)?);
+            env.delete_local_ref(item)?;
+        }
+        return Ok(ExternalValue::List(values));
+    }
+    if env.is_instance_of(value, "java/util/Map")? {
+        let keys = env.call_method(value, "keySet", "()Ljava/util/Set;", &[])?.l()?;
+        let keys = JObjectArray::from(env.call_method(&keys, "toArray", "()[Ljava/lang/Object;", &[])?.l()?);
+        let mut values = BTreeMap::new();
+        for i in 0..env.get_array_length(&keys)? {
+            let key = env.get_object_array_element(&keys, i)?;
+            let item = env
+                .call_method(value, "get", "(Ljava/lang/Object;)Ljava/lang/Object;", &[JValue::Object(&key)])?
+                .l()?;
+            let name = env.call_method(&key, "toString", "()Ljava/lang/String;", &[])?.l()?;
+            let name = String::from(env.get_string(&JString::from(name))?);
+            values.insert(name, from_java(env, &item)?);
+            env.delete_local_ref(key)?;
+            env.delete_local_ref(item)?;
+        }
+        return Ok(ExternalValue::Object(values));
+    }
+    let class = env.get_object_class(value)?;
+    let class = env.call_method(&class, "getName", "()Ljava/lang/String;", &[])?.l()?;
+    let class = String::from(env.get_string(&JString::from(class))?);
+    env.throw_new("java/lang/IllegalArgumentException", format!("Unsupported host value {class}"))?;
+    Err(JniError::JavaException)
+}
+
+/// Answers `ExternalInterface.call` with the host's `onExternalCall`, for the names it registered.
+pub struct AndroidExternalInterface {
+    host: JavaHost,
+}
+
+impl AndroidExternalInterface {
+    pub fn new(host: JavaHost) -> Self {
+        Self { host }
+    }
+}
+
+impl ExternalInterfaceProvider for AndroidExternalInterface {
+    fn call_method(&self, _context: &mut UpdateContext<'_>, name: &str, args: &[ExternalValue]) -> ExternalValue {
+        if !HOST_CALLBACKS.lock().unwrap().contains(name) {
+            warn!("ExternalInterface.call(\"{name}\") has no host callback.");
+            return ExternalValue::Undefined;
+        }
+        let result = self.host.with_env(|env| {
+            let name = env.new_string(name)?;
+            let values = env.new_object_array(args.len() as jsize, "java/lang/Object", JObject::null())?;
+            for (i, arg) in args.iter().enumerate() {
+                let arg = to_java(env, arg)?;
+                env.set_object_array_element(&values, i as jsize, &arg)?;
+                env.delete_local_ref(arg)?;
+            }
+            let result = env
+                .call_method(
+                    self.host.thiz(),
+                    "onExternalCall",
+                    "(Ljava/lang/String;[Ljava/lang/Object;)Ljava/lang/Object;",
+                    &[JValue::Object(&name), JValue::Object(&values)],
+                )?
+                .l()?;
+            from_java(env, &result)
+        });
+        result.unwrap_or_else(|e| {
+            error!("Failed to call host callback {name}! {e}");
+            ExternalValue::Undefined
+        })
+    }
+
+    /// The movie registered `name` with `ExternalInterface.addCallback`.
+    fn on_callback_available(&self, name: &str) {
+        self.host.call_with_str("onExternalCallbackAvailable", name);
+    }
+
+    fn get_id(&self) -> Option<String> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jni::{InitArgsBuilder, JavaVM};
+
+    use super::*;
+
+    static JVM: Lazy<JavaVM> = Lazy::new(|| JavaVM::new(InitArgsBuilder::new().build().unwrap()).unwrap());
+
+    fn round_trip(value: &ExternalValue) -> ExternalValue {
+        let mut env = JVM.attach_current_thread().unwrap();
+        env.with_local_frame(8, |env| {
+            let object = to_java(env, value)?;
+            from_java(env, &object)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn host_callbacks_can_be_unregistered() {
+        set_host_callback("saveScore".to_string(), true);
+        set_host_callback("loadScore".to_string(), true);
+        set_host_callback("saveScore".to_string(), false);
+        let callbacks = HOST_CALLBACKS.lock().unwrap();
+        assert!(!callbacks.contains("saveScore"));
+        assert!(callbacks.contains("loadScore"));
+    }
+
+    #[test]
+    fn undefined_and_null_both_become_null() {
+        assert_eq!(round_trip(&ExternalValue::Undefined), ExternalValue::Null);
+        assert_eq!(round_trip(&ExternalValue::Null), ExternalValue::Null);
+    }
+
+    #[test]
+    fn marshals_scalars() {
+        for value in [
+            ExternalValue::Bool(true),
+            ExternalValue::Bool(false),
+            ExternalValue::Number(1250.5),
+            ExternalValue::Number(-3.0),
+            ExternalValue::String(String::new()),
+            ExternalValue::String("Ünïcode \"hero\" 🎮".to_string()),
+        ] {
+            assert_eq!(round_trip(&value), value);
+        }
+    }
+
+    #[test]
+    fn keeps_non_finite_numbers() {
+        assert!(matches!(round_trip(&ExternalValue::Number(f64::NAN)), ExternalValue::Number(n) if n.is_nan()));
+        assert_eq!(round_trip(&ExternalValue::Number(f64::INFINITY)), ExternalValue::Number(f64::INFINITY));
+        assert_eq!(round_trip(&ExternalValue::Number(f64::NEG_INFINITY)), ExternalValue::Number(f64::NEG_INFINITY));
+    }
+
+    #[test]
+    fn marshals_nested_lists_and_objects() {
+        let value = ExternalValue::Object(BTreeMap::from([
+            ("score".to_string(), ExternalValue::Number(1250.5)),
+            ("name".to_string(), ExternalValue::String("hero".to_string())),
+            (
+                "items".to_string(),
+                ExternalValue::List(vec![
+                    ExternalValue::Bool(true),
+                    ExternalValue::Null,
+                    ExternalValue::List(vec![ExternalValue::Number(-3.0)]),
+                    ExternalValue::Object(BTreeMap::from([("hard".to_string(), ExternalValue::Bool(false))])),
+                ]),
+            ),
+            ("empty".to_string(), ExternalValue::Object(BTreeMap::new())),
+        ]));
+        assert_eq!(round_trip(&value), value);
+        assert_eq!(round_trip(&ExternalValue::List(Vec::new())), ExternalValue::List(Vec::new()));
+    }
+
+    #[test]
+    fn unsupported_host_values_throw() {
+        let mut env = JVM.attach_current_thread().unwrap();
+        let object = env.new_object("java/lang/Object", "()V", &[]).unwrap();
+        assert!(matches!(from_java(&mut env, &object), Err(JniError::JavaException)));
+        assert!(env.exception_check().unwrap());
+        env.exception_clear().unwrap();
+    }
+}
@@ -0,0 +1,178 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Seek, SeekFrom, Write},
+    path::Path,
+    sync::{
+        Mutex,
+        atomic::{AtomicI32, Ordering},
+    },
+};
+
+use chrono::{DateTime, Utc};
+use log::error;
+use once_cell::sync::Lazy;
+use ruffle_core::backend::ui::{FileDialogResult, FileFilter};
+use tokio::sync::oneshot;
+
+/// What the host's picker came back with.
+pub enum DialogAnswer {
+    Cancelled,
+    /// A document picked for `FileReference.browse`, read in full by the host.
+    Opened { name: String, contents: Vec<u8> },
+    /// A document created for `FileReference.save`, with a descriptor open for writing.
+    Created { name: String, file: File },
+}
+
+static PENDING: Lazy<Mutex<HashMap<i32, oneshot::Sender<DialogAnswer>>>> = Lazy::new(Default::default);
+static NEXT_ID: AtomicI32 = AtomicI32::new(0);
+
+/// Registers a dialog the host is about to show, the id goes back with its answer.
+pub fn request() -> (i32, oneshot::Receiver<DialogAnswer>) {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let (sender, receiver) = oneshot::channel();
+    PENDING.lock().unwrap().insert(id, sender);
+    (id, receiver)
+}
+
+pub fn answer(id: i32, answer: DialogAnswer) {
+    match PENDING.lock().unwrap().remove(&id) {
+        Some(sender) => {
+            // The movie may have stopped waiting, then the answer is simply dropped.
+            let _ = sender.send(answer);
+        }
+        None => error!("Answer for unknown file dialog {id}!"),
+    }
+}
+
+/// Drops every pending dialog, whoever awaits one sees it cancelled.
+pub fn cancel_all() {
+    PENDING.lock().unwrap().clear();
+}
+
+/// Extensions the picker should offer, without the `*.` Flash writes them with.
+pub fn filter_extensions(filters: &[FileFilter]) -> Vec<String> {
+    filters
+        .iter()
+        .flat_map(|filter| filter.extensions.split(';'))
+        .map(|extension| extension.trim().trim_start_matches('*').trim_start_matches('.'))
+        .filter(|extension| !extension.is_empty() && *extension != "*")
+        .map(str::to_lowercase)
+        .collect()
+}
+
+pub struct AndroidFileDialogResult {
+    name: Option<String>,
+    contents: Vec<u8>,
+    file: Option<File>,
+}
+
+impl From<DialogAnswer> for AndroidFileDialogResult {
+    fn from(answer: DialogAnswer) -> Self {
+        match answer {
+            DialogAnswer::Cancelled => Self {
+                name: None,
+                contents: Vec::new(),
+                file: None,
+            },
+            DialogAnswer::Opened { name, contents } => Self {
+                name: Some(name),
+                contents,
+                file: None,
+            },
+            DialogAnswer::Created { name, file } => Self {
+                name: Some(name),
+                contents: Vec::new(),
+                file: Some(file),
+            },
+        }
+    }
+}
+
+impl FileDialogResult for AndroidFileDialogResult {
+    fn is_cancelled(&self) -> bool {
+        self.name.is_none()
+    }
+
+    fn creation_time(&self) -> Option<DateTime<Utc>> {
+        None
+    }
+
+    fn modification_time(&self) -> Option<DateTime<Utc>> {
+        None
+    }
+
+    fn file_name(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    fn size(&self) -> Option<u64> {
+        self.name.as_ref().map(|_| self.contents.len() as u64)
+    }
+
+    fn file_type(&self) -> Option<String> {
+        let name = self.name.as_ref()?;
+        Path::new(name)
+            .extension()
+            .map(|extension| format!(".{}", extension.to_string_lossy()))
+    }
+
+    fn creator(&self) -> Option<String> {
+        None
+    }
+
+    fn contents(&self) -> &[u8] {
+        &self.contents
+    }
+
+    fn write_and_refresh(&mut self, data: &[u8]) {
+        let Some(file) = &mut self.file else {
+            error!("File dialog result is not writable!");
+            return;
+        };
+        let result = file
+            .set_len(0)
+            .and_then(|_| file.seek(SeekFrom::Start(0)))
+            .and_then(|_| file.write_all(data))
+            .and_then(|_| file.sync_all());
+        match result {
+            Ok(()) => self.contents = data.to_vec(),
+            Err(e) => error!("Failed to write {}! {e}", self.name.as_deref().unwrap_or_default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(extensions: &str) -> FileFilter {
+        FileFilter {
+            description: String::new(),
+            extensions: extensions.to_string(),
+            mac_type: None,
+        }
+    }
+
+    #[test]
+    fn flattens_flash_filters() {
+        assert_eq!(
+            filter_extensions(&[filter("*.jpg;*.PNG"), filter(" *.lvl ; *.*")]),
+            ["jpg", "png", "lvl"]
+        );
+        assert!(filter_extensions(&[filter("*.*")]).is_empty());
+    }
+
+    #[test]
+    fn answers_reach_their_dialog() {
+        let (first, mut first_receiver) = request();
+        let (second, mut second_receiver) = request();
+        answer(second, DialogAnswer::Opened { name: "level.lvl".into(), contents: vec![1, 2, 3] });
+        answer(first, DialogAnswer::Cancelled);
+        let result = AndroidFileDialogResult::from(second_receiver.try_recv().unwrap());
+        assert_eq!(result.file_name().as_deref(), Some("level.lvl"));
+        assert_eq!(result.file_type().as_deref(), Some(".lvl"));
+        assert_eq!((result.size(), result.contents()), (Some(3), &[1, 2, 3][..]));
+        assert!(AndroidFileDialogResult::from(first_receiver.try_recv().unwrap()).is_cancelled());
+    }
+}
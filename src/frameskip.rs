@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+pub const DEFAULT_MAX_SKIPS: u32 = 3;
+
+/// Drops render passes, never ticks, while the worker loop cannot keep up with
+/// the movie. A frame is skipped when ticking it plus what the last render took
+/// would overrun the frame budget, but never more than `max_skips` in a row.
+pub struct FrameSkipper {
+    pub enabled: bool,
+    pub max_skips: u32,
+    last_render: Duration,
+    consecutive: u32,
+    pub rendered: u64,
+    pub skipped: u64,
+}
+
+impl FrameSkipper {
+    pub fn new(enabled: bool, max_skips: u32) -> Self {
+        Self {
+            enabled,
+            max_skips,
+            last_render: Duration::ZERO,
+            consecutive: 0,
+            rendered: 0,
+            skipped: 0,
+        }
+    }
+
+    /// `tick_time` is what ticking took in this iteration, `budget` the length of a movie frame.
+    pub fn should_render(&mut self, tick_time: Duration, budget: Duration) -> bool {
+        if self.enabled && self.consecutive < self.max_skips && tick_time + self.last_render > budget {
+            self.consecutive += 1;
+            self.skipped += 1;
+            return false;
+        }
+        true
+    }
+
+    pub fn on_rendered(&mut self, render_time: Duration) {
+        self.last_render = render_time;
+        self.consecutive = 0;
+        self.rendered += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUDGET: Duration = Duration::from_millis(16);
+
+    #[test]
+    fn skips_while_over_budget() {
+        let mut skipper = FrameSkipper::new(true, DEFAULT_MAX_SKIPS);
+        assert!(skipper.should_render(Duration::from_millis(5), BUDGET));
+        skipper.on_rendered(Duration::from_millis(12));
+        assert!(!skipper.should_render(Duration::from_millis(5), BUDGET));
+        assert!(!skipper.should_render(Duration::from_millis(5), BUDGET));
+        assert!(!skipper.should_render(Duration::from_millis(5), BUDGET));
+        // The screen still updates every few frames.
+        assert!(skipper.should_render(Duration::from_millis(5), BUDGET));
+        skipper.on_rendered(Duration::from_millis(4));
+        assert!(skipper.should_render(Duration::from_millis(5), BUDGET));
+        assert_eq!((skipper.rendered, skipper.skipped), (2, 3));
+    }
+
+    #[test]
+    fn never_skips_when_disabled() {
+        let mut skipper = FrameSkipper::new(false, DEFAULT_MAX_SKIPS);
+        skipper.on_rendered(Duration::from_millis(40));
+        assert!(skipper.should_render(Duration::from_millis(40), BUDGET));
+        assert_eq!(skipper.skipped, 0);
+    }
+}
@@ -0,0 +1,156 @@
+use ruffle_core::{StageScaleMode, external::FsCommandProvider};
+
+/// A projector command from `fscommand`, see `FsCommand::parse`.
+#[derive(PartialEq, Clone, Debug)]
+pub enum FsCommand {
+    /// Ends the session.
+    Quit,
+    FullScreen(bool),
+    /// `false` shows the stage at 100% instead of scaling it to the surface.
+    AllowScale(bool),
+    /// Left to the host, which has no context menu unless it draws one.
+    ShowMenu(bool),
+    /// Left to the host, the player never runs programs itself.
+    Exec(String),
+    Other { command: String, args: String },
+}
+
+impl FsCommand {
+    pub fn parse(command: &str, args: &str) -> Self {
+        let enabled = parse_bool(args);
+        match (command.trim().to_lowercase().as_str(), enabled) {
+            ("quit", _) => FsCommand::Quit,
+            ("fullscreen", Some(enabled)) => FsCommand::FullScreen(enabled),
+            ("allowscale", Some(enabled)) => FsCommand::AllowScale(enabled),
+            ("showmenu", Some(enabled)) => FsCommand::ShowMenu(enabled),
+            ("exec", _) => FsCommand::Exec(args.trim().to_string()),
+            _ => FsCommand::Other {
+                command: command.to_string(),
+                args: args.to_string(),
+            },
+        }
+    }
+}
+
+/// What the worker loop does for a command.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum FsAction {
+    /// Stops the worker like `em_stop` does, then tells the host.
+    Stop,
+    SetFullscreen(bool),
+    SetScaleMode(StageScaleMode),
+    /// Handed to the host as is.
+    Forward,
+}
+
+impl FsCommand {
+    pub fn action(&self) -> FsAction {
+        match self {
+            FsCommand::Quit => FsAction::Stop,
+            FsCommand::FullScreen(enabled) => FsAction::SetFullscreen(*enabled),
+            FsCommand::AllowScale(true) => FsAction::SetScaleMode(StageScaleMode::ShowAll),
+            FsCommand::AllowScale(false) => FsAction::SetScaleMode(StageScaleMode::NoScale),
+            FsCommand::ShowMenu(_) | FsCommand::Exec(_) | FsCommand::Other { .. } => FsAction::Forward,
+        }
+    }
+}
+
+/// Projectors took `true` and `false` in any case, and a few movies pass numbers.
+fn parse_bool(args: &str) -> Option<bool> {
+    match args.trim().to_lowercase().as_str() {
+        "true" | "1" | "yes" => Some(true),
+        "false" | "0" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+/// Runs inside the player, so commands are handed to the worker loop as events.
+pub struct AndroidFsCommands<F: Fn(String, String)> {
+    dispatch: F,
+}
+
+impl<F: Fn(String, String)> AndroidFsCommands<F> {
+    pub fn new(dispatch: F) -> Self {
+        Self { dispatch }
+    }
+}
+
+impl<F: Fn(String, String)> FsCommandProvider for AndroidFsCommands<F> {
+    fn on_fs_command(&self, command: &str, args: &str) -> bool {
+        (self.dispatch)(command.to_string(), args.to_string());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_quit() {
+        assert_eq!(FsCommand::parse("quit", ""), FsCommand::Quit);
+        assert_eq!(FsCommand::parse("QUIT", "anything"), FsCommand::Quit);
+    }
+
+    #[test]
+    fn parses_fullscreen() {
+        assert_eq!(FsCommand::parse("fullscreen", "true"), FsCommand::FullScreen(true));
+        assert_eq!(FsCommand::parse("fullScreen", " False "), FsCommand::FullScreen(false));
+    }
+
+    #[test]
+    fn parses_allowscale() {
+        assert_eq!(FsCommand::parse("allowscale", "false"), FsCommand::AllowScale(false));
+        assert_eq!(FsCommand::parse("allowscale", "1"), FsCommand::AllowScale(true));
+    }
+
+    #[test]
+    fn parses_showmenu() {
+        assert_eq!(FsCommand::parse("showmenu", "FALSE"), FsCommand::ShowMenu(false));
+        assert_eq!(FsCommand::parse("showmenu", "true"), FsCommand::ShowMenu(true));
+    }
+
+    #[test]
+    fn parses_exec() {
+        assert_eq!(FsCommand::parse("exec", " launcher.exe "), FsCommand::Exec("launcher.exe".to_string()));
+    }
+
+    #[test]
+    fn keeps_unknown_commands_and_arguments() {
+        assert_eq!(
+            FsCommand::parse("trapallkeys", "true"),
+            FsCommand::Other { command: "trapallkeys".to_string(), args: "true".to_string() }
+        );
+        assert_eq!(
+            FsCommand::parse("fullscreen", "maybe"),
+            FsCommand::Other { command: "fullscreen".to_string(), args: "maybe".to_string() }
+        );
+    }
+
+    #[test]
+    fn quit_stops_the_worker() {
+        assert_eq!(FsCommand::parse("quit", "").action(), FsAction::Stop);
+    }
+
+    #[test]
+    fn allowscale_sets_the_scale_mode() {
+        assert_eq!(FsCommand::parse("allowscale", "true").action(), FsAction::SetScaleMode(StageScaleMode::ShowAll));
+        assert_eq!(FsCommand::parse("allowscale", "false").action(), FsAction::SetScaleMode(StageScaleMode::NoScale));
+    }
+
+    #[test]
+    fn fullscreen_and_the_rest_go_to_the_host() {
+        assert_eq!(FsCommand::parse("fullscreen", "true").action(), FsAction::SetFullscreen(true));
+        assert_eq!(FsCommand::parse("showmenu", "false").action(), FsAction::Forward);
+        assert_eq!(FsCommand::parse("exec", "launcher.exe").action(), FsAction::Forward);
+        assert_eq!(FsCommand::parse("trapallkeys", "true").action(), FsAction::Forward);
+    }
+
+    #[test]
+    fn provider_hands_commands_over() {
+        let received = std::cell::RefCell::new(Vec::new());
+        let provider = AndroidFsCommands::new(|command, args| received.borrow_mut().push((command, args)));
+        assert!(provider.on_fs_command("allowscale", "false"));
+        assert_eq!(received.into_inner(), [("allowscale".to_string(), "false".to_string())]);
+    }
+}
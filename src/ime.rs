@@ -0,0 +1,128 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use ruffle_core::{PlayerEvent, events::TextControlCode};
+
+/// Whether a text field has focus, going by Ruffle asking for the virtual keyboard.
+static TEXT_FOCUS: AtomicBool = AtomicBool::new(false);
+
+pub fn set_text_focus(focused: bool) {
+    TEXT_FOCUS.store(focused, Ordering::Relaxed);
+}
+
+pub fn has_text_focus() -> bool {
+    TEXT_FOCUS.load(Ordering::Relaxed)
+}
+
+/// What the host's `InputConnection` hands over while a text field has focus.
+#[derive(Clone, Debug)]
+pub enum TextEvent {
+    /// `commitText`: final text replacing the composing region.
+    Commit(String),
+    /// `setComposingText`: provisional text, replaced by the next update.
+    Composing(String),
+    /// `deleteSurroundingText`: characters before and after the caret.
+    DeleteSurrounding(usize, usize),
+}
+
+/// Ruffle's text fields only take typed characters and editing keys, so the
+/// composing region is typed out as it changes and erased again when revised.
+#[derive(Default)]
+pub struct TextComposer {
+    composing: Vec<char>,
+}
+
+impl TextComposer {
+    pub fn on_text_event(&mut self, event: TextEvent) -> Vec<PlayerEvent> {
+        match event {
+            TextEvent::Commit(text) => {
+                let events = self.compose(&text);
+                self.composing.clear();
+                events
+            }
+            TextEvent::Composing(text) => self.compose(&text),
+            TextEvent::DeleteSurrounding(before, after) => {
+                self.composing.clear();
+                let mut events = control(TextControlCode::Backspace, before);
+                events.extend(control(TextControlCode::Delete, after));
+                events
+            }
+        }
+    }
+
+    /// Replaces the composing region with `text`, retyping only what differs.
+    fn compose(&mut self, text: &str) -> Vec<PlayerEvent> {
+        let text: Vec<char> = text.chars().collect();
+        let common = self
+            .composing
+            .iter()
+            .zip(&text)
+            .take_while(|(old, new)| old == new)
+            .count();
+        let mut events = control(TextControlCode::Backspace, self.composing.len() - common);
+        for c in &text[common..] {
+            match c {
+                '\n' => events.push(PlayerEvent::TextControl {
+                    code: TextControlCode::Enter,
+                }),
+                c if c.is_control() => (),
+                c => events.push(PlayerEvent::TextInput { codepoint: *c }),
+            }
+        }
+        self.composing = text;
+        events
+    }
+}
+
+fn control(code: TextControlCode, count: usize) -> Vec<PlayerEvent> {
+    (0..count).map(|_| PlayerEvent::TextControl { code }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn composing_retypes_only_the_changed_tail() {
+        let mut composer = TextComposer::default();
+        let out = composer.on_text_event(TextEvent::Composing("he".into()));
+        assert!(matches!(
+            out[..],
+            [PlayerEvent::TextInput { codepoint: 'h' }, PlayerEvent::TextInput { codepoint: 'e' }]
+        ));
+        let out = composer.on_text_event(TextEvent::Composing("hi".into()));
+        assert!(matches!(
+            out[..],
+            [
+                PlayerEvent::TextControl { code: TextControlCode::Backspace },
+                PlayerEvent::TextInput { codepoint: 'i' }
+            ]
+        ));
+        // Committing what is already composed types nothing new.
+        assert!(composer.on_text_event(TextEvent::Commit("hi".into())).is_empty());
+        let out = composer.on_text_event(TextEvent::Commit("!\n".into()));
+        assert!(matches!(
+            out[..],
+            [
+                PlayerEvent::TextInput { codepoint: '!' },
+                PlayerEvent::TextControl { code: TextControlCode::Enter }
+            ]
+        ));
+    }
+
+    #[test]
+    fn delete_surrounding_ends_composition() {
+        let mut composer = TextComposer::default();
+        composer.on_text_event(TextEvent::Composing("ab".into()));
+        let out = composer.on_text_event(TextEvent::DeleteSurrounding(1, 1));
+        assert!(matches!(
+            out[..],
+            [
+                PlayerEvent::TextControl { code: TextControlCode::Backspace },
+                PlayerEvent::TextControl { code: TextControlCode::Delete }
+            ]
+        ));
+        // The composing region is gone, so new text is not diffed against it.
+        let out = composer.on_text_event(TextEvent::Composing("a".into()));
+        assert!(matches!(out[..], [PlayerEvent::TextInput { codepoint: 'a' }]));
+    }
+}
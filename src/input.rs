@@ -1,18 +1,30 @@
 use std::{
     collections::HashMap,
     fmt::Display,
+    fs,
     i32,
-    sync::{
-        MutexGuard,
-        atomic::{self, AtomicBool},
-    },
+    path::Path,
+    sync::MutexGuard,
+    time::Instant,
 };
 
 use ndk::event::Keycode;
 use once_cell::sync::Lazy;
 use ruffle_core::{
     Player, PlayerEvent,
-    events::{KeyDescriptor, KeyLocation, LogicalKey, MouseButton, NamedKey, PhysicalKey},
+    events::{
+        KeyDescriptor, KeyLocation, LogicalKey, MouseButton, MouseWheelDelta, NamedKey,
+        PhysicalKey, TextControlCode,
+    },
+};
+
+use crate::{
+    ime::{self, TextComposer, TextEvent},
+    navigation::{FocusNavigator, NavAction},
+    repeat::{BindingMode, KeyRepeater, RepeatConfig},
+    speed::Hotkey,
+    stick::{AnalogStick, JoystickEvent, StickConfig, StickMode},
+    touch::{TouchConfig, TouchTranslator},
 };
 
 // pub const RETRO_DEVICE_JOYPAD: i32 = 1;
@@ -70,6 +82,15 @@ impl From<i32> for KeyAction {
     }
 }
 
+impl From<KeyAction> for i32 {
+    fn from(action: KeyAction) -> Self {
+        match action {
+            KeyAction::Down => 0,
+            KeyAction::Up => 1,
+        }
+    }
+}
+
 impl From<bool> for KeyAction {
     fn from(is_down: bool) -> Self {
         if is_down {
@@ -80,851 +101,1296 @@ impl From<bool> for KeyAction {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(PartialEq, Clone, Copy, Debug)]
 pub struct KeyEvent {
     key: Keycode,
-    action: KeyAction
+    action: KeyAction,
+    device_id: i32,
+    meta_state: u32,
+    /// `KeyEvent.getUnicodeChar()`, already resolved against the meta state.
+    unicode_char: u32,
+    /// `KeyEvent.getRepeatCount()`, non-zero for the key downs Android repeats on its own.
+    repeat_count: i32,
 }
 
 impl Display for KeyEvent {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "KeyEvent: {{ key={}, action={} }}",
-            keycode_as_string(&self.key),
+            "KeyEvent: {{ key={:?}, action={} }}",
+            self.key,
             self.action
         )
     }
 }
 
 impl KeyEvent {
-    pub fn new(key: Keycode, action: KeyAction) -> Self {
+    pub fn new(key: Keycode, action: KeyAction, device_id: i32) -> Self {
         Self {
             key,
-            action
+            action,
+            device_id,
+            meta_state: 0,
+            unicode_char: 0,
+            repeat_count: 0,
+        }
+    }
+
+    pub fn with_text(mut self, meta_state: u32, unicode_char: u32) -> Self {
+        self.meta_state = meta_state;
+        self.unicode_char = unicode_char;
+        self
+    }
+
+    pub fn with_repeat_count(mut self, repeat_count: i32) -> Self {
+        self.repeat_count = repeat_count;
+        self
+    }
+
+    pub fn key(&self) -> Keycode {
+        self.key
+    }
+
+    pub fn action(&self) -> KeyAction {
+        self.action
+    }
+
+    pub fn device_id(&self) -> i32 {
+        self.device_id
+    }
+
+    pub fn meta_state(&self) -> u32 {
+        self.meta_state
+    }
+
+    pub fn unicode_char(&self) -> u32 {
+        self.unicode_char
+    }
+
+    pub fn repeat_count(&self) -> i32 {
+        self.repeat_count
+    }
+
+    fn is_repeat(&self) -> bool {
+        self.action == KeyAction::Down && self.repeat_count > 0
+    }
+
+    /// The printable character this key types, unless a shortcut modifier is held.
+    /// Right Alt is AltGr on most layouts, so only left Alt counts as a shortcut.
+    fn text(&self) -> Option<char> {
+        if self.meta_state & (META_CTRL_ON | META_ALT_LEFT_ON | META_META_ON) != 0 {
+            return None;
+        }
+        char::from_u32(self.unicode_char).filter(|c| *c != '\0' && !c.is_control())
+    }
+
+    fn num_lock(&self) -> bool {
+        self.meta_state & META_NUM_LOCK_ON != 0
+    }
+}
+
+const META_SHIFT_ON: u32 = 0x1;
+const META_ALT_LEFT_ON: u32 = 0x10;
+const META_CTRL_ON: u32 = 0x1000;
+const META_META_ON: u32 = 0x10000;
+const META_NUM_LOCK_ON: u32 = 0x200000;
+
+/// Masked `MotionEvent` actions.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum TouchAction {
+    Down,
+    Up,
+    Move,
+    Cancel,
+    PointerDown,
+    PointerUp,
+    Other,
+}
+
+impl From<i32> for TouchAction {
+    fn from(action: i32) -> Self {
+        match action {
+            0 => TouchAction::Down,
+            1 => TouchAction::Up,
+            2 => TouchAction::Move,
+            3 => TouchAction::Cancel,
+            5 => TouchAction::PointerDown,
+            6 => TouchAction::PointerUp,
+            _ => TouchAction::Other,
+        }
+    }
+}
+
+impl From<TouchAction> for i32 {
+    fn from(action: TouchAction) -> Self {
+        match action {
+            TouchAction::Down => 0,
+            TouchAction::Up => 1,
+            TouchAction::Move => 2,
+            TouchAction::Cancel => 3,
+            TouchAction::PointerDown => 5,
+            TouchAction::PointerUp => 6,
+            TouchAction::Other => -1,
         }
     }
 }
 
 #[derive(Clone, Copy, Debug)]
-pub struct TouchEvent {
+pub struct TouchPointer {
+    pub id: i32,
     pub x: f64,
     pub y: f64,
-    pub action: KeyAction,
+}
+
+/// A whole `MotionEvent`: every pointer currently on screen, plus the one
+/// the action applies to. Coordinates are in viewport pixels.
+#[derive(Clone, Debug)]
+pub struct TouchEvent {
+    pub action: TouchAction,
+    pub pointer_id: i32,
+    pub pointers: Vec<TouchPointer>,
 }
 
 impl TouchEvent {
-    pub fn new(x: f64, y: f64, action: KeyAction) -> Self {
-        Self { x, y, action }
+    pub fn new(action: TouchAction, pointer_id: i32, pointers: Vec<TouchPointer>) -> Self {
+        Self {
+            action,
+            pointer_id,
+            pointers,
+        }
     }
 }
 
-static KEYCODE_DESCRIPTORS: Lazy<HashMap<i32, KeyDescriptor>> = Lazy::new(|| {
+/// Masked `MotionEvent` actions seen on the mouse and stylus path.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum PointerAction {
+    Down,
+    Up,
+    Move,
+    HoverMove,
+    Scroll,
+    HoverEnter,
+    HoverExit,
+    ButtonPress,
+    ButtonRelease,
+    Other,
+}
+
+impl From<i32> for PointerAction {
+    fn from(action: i32) -> Self {
+        match action {
+            0 => PointerAction::Down,
+            1 => PointerAction::Up,
+            2 => PointerAction::Move,
+            7 => PointerAction::HoverMove,
+            8 => PointerAction::Scroll,
+            9 => PointerAction::HoverEnter,
+            10 => PointerAction::HoverExit,
+            11 => PointerAction::ButtonPress,
+            12 => PointerAction::ButtonRelease,
+            _ => PointerAction::Other,
+        }
+    }
+}
+
+impl From<PointerAction> for i32 {
+    fn from(action: PointerAction) -> Self {
+        match action {
+            PointerAction::Down => 0,
+            PointerAction::Up => 1,
+            PointerAction::Move => 2,
+            PointerAction::HoverMove => 7,
+            PointerAction::Scroll => 8,
+            PointerAction::HoverEnter => 9,
+            PointerAction::HoverExit => 10,
+            PointerAction::ButtonPress => 11,
+            PointerAction::ButtonRelease => 12,
+            PointerAction::Other => -1,
+        }
+    }
+}
+
+/// Everything the host feeds into the input layer.
+#[derive(Clone, Debug)]
+pub enum InputEvent {
+    Key(KeyEvent),
+    Touch(TouchEvent),
+    Pointer(PointerEvent),
+    Joystick(JoystickEvent),
+    Text(TextEvent),
+}
+
+pub const BUTTON_PRIMARY: i32 = 1;
+pub const BUTTON_SECONDARY: i32 = 2;
+pub const BUTTON_TERTIARY: i32 = 4;
+pub const BUTTON_STYLUS_PRIMARY: i32 = 32;
+pub const BUTTON_STYLUS_SECONDARY: i32 = 64;
+
+const POINTER_BUTTONS: [(i32, MouseButton); 5] = [
+    (BUTTON_PRIMARY, MouseButton::Left),
+    (BUTTON_SECONDARY, MouseButton::Right),
+    (BUTTON_TERTIARY, MouseButton::Middle),
+    (BUTTON_STYLUS_PRIMARY, MouseButton::Right),
+    (BUTTON_STYLUS_SECONDARY, MouseButton::Middle),
+];
+
+/// A `MotionEvent` from a physical mouse or a stylus, in viewport pixels.
+#[derive(Clone, Copy, Debug)]
+pub struct PointerEvent {
+    pub action: PointerAction,
+    pub x: f64,
+    pub y: f64,
+    /// `MotionEvent.getButtonState()`.
+    pub button_state: i32,
+    /// `AXIS_VSCROLL`, positive when scrolling up.
+    pub scroll: f64,
+}
+
+impl PointerEvent {
+    pub fn new(action: PointerAction, x: f64, y: f64, button_state: i32, scroll: f64) -> Self {
+        Self {
+            action,
+            x,
+            y,
+            button_state,
+            scroll,
+        }
+    }
+
+    /// Stylus contact reports no buttons, so treat the tip as the primary one.
+    fn effective_buttons(&self) -> i32 {
+        match self.action {
+            PointerAction::Down | PointerAction::Move if self.button_state == 0 => BUTTON_PRIMARY,
+            PointerAction::Up => self.button_state & !BUTTON_PRIMARY,
+            _ => self.button_state,
+        }
+    }
+}
+
+/// `Keycode` variant names, as used in remap profiles.
+static KEYCODE_NAMES: Lazy<HashMap<String, Keycode>> = Lazy::new(|| {
     let mut it = HashMap::new();
-    for keycode in KEYCODE_ARRAY {
-        it.insert(keycode.into(), keycode_as_descriptor(&keycode));
+    for code in 0..=MAX_KEYCODE {
+        let keycode = Keycode::from(code);
+        if !matches!(keycode, Keycode::__Unknown(_)) {
+            it.insert(format!("{keycode:?}"), keycode);
+        }
     }
     it
 });
 
-static POINTER_ACTION: AtomicBool = AtomicBool::new(false);
+const MAX_KEYCODE: i32 = 320;
+
+/// Key bindings, one per line or separated by commas, e.g. `ButtonA -> Space, DpadUp -> W`.
+/// Names are `Keycode` variants. A key bound more than once presses every target,
+/// keys without a binding pass through unchanged. A binding may end with a mode,
+/// e.g. `ButtonX -> Z [turbo]`, which then applies to every target of that key.
+/// A target starting with `@` is a hotkey handled by the host, e.g. `ButtonL2 -> @fast_forward`.
+#[derive(Clone, Debug, Default)]
+pub struct RemapProfile {
+    bindings: HashMap<i32, Binding>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Binding {
+    targets: Vec<Keycode>,
+    mode: BindingMode,
+    hotkeys: Vec<Hotkey>,
+}
+
+impl RemapProfile {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut profile = Self::default();
+        for entry in text.split(['\n', ',']) {
+            let entry = entry.split('#').next().unwrap_or_default().trim();
+            if entry.is_empty() {
+                continue;
+            }
+            let Some((source, target)) = ["->", "→", "="]
+                .iter()
+                .find_map(|separator| entry.split_once(separator))
+            else {
+                return Err(format!("Missing '->' in binding \"{entry}\""));
+            };
+            let source = keycode_from_name(source.trim())?;
+            let (target, mode) = match target.trim().strip_suffix(']') {
+                Some(target) => {
+                    let Some((target, mode)) = target.split_once('[') else {
+                        return Err(format!("Missing '[' in binding \"{entry}\""));
+                    };
+                    (target, Some(BindingMode::try_from(mode.trim())?))
+                }
+                None => (target, None),
+            };
+            let target = target.trim();
+            let binding = profile.bindings.entry(source.into()).or_default();
+            match target.strip_prefix('@') {
+                Some(hotkey) => binding.hotkeys.push(Hotkey::try_from(hotkey)?),
+                None => binding.targets.push(keycode_from_name(target)?),
+            }
+            if let Some(mode) = mode {
+                binding.mode = mode;
+            }
+        }
+        Ok(profile)
+    }
+
+    /// Accepts either the profile itself or the path of a file containing it.
+    pub fn load(source: &str) -> Result<Self, String> {
+        let path = Path::new(source.trim());
+        if path.is_file() {
+            let text = fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+            Self::parse(&text)
+        } else {
+            Self::parse(source)
+        }
+    }
+
+    pub fn map(&self, key: Keycode) -> Vec<Keycode> {
+        self.bindings
+            .get(&key.into())
+            .map_or_else(|| vec![key], |binding| binding.targets.clone())
+    }
+
+    pub fn mode(&self, key: Keycode) -> BindingMode {
+        self.bindings
+            .get(&key.into())
+            .map_or(BindingMode::Normal, |binding| binding.mode)
+    }
+
+    pub fn hotkeys(&self, key: Keycode) -> &[Hotkey] {
+        self.bindings
+            .get(&key.into())
+            .map_or(&[], |binding| &binding.hotkeys)
+    }
+}
+
+pub fn keycode_from_name(name: &str) -> Result<Keycode, String> {
+    KEYCODE_NAMES
+        .get(name)
+        .copied()
+        .ok_or_else(|| format!("Unknown key \"{name}\""))
+}
+
+/// Profiles for specific input devices, falling back to a default one.
+#[derive(Default)]
+pub struct KeyRemapper {
+    default_profile: Option<RemapProfile>,
+    device_profiles: HashMap<i32, RemapProfile>,
+}
+
+impl KeyRemapper {
+    /// `None` as the device sets the default profile, `None` as the profile clears it.
+    pub fn set_profile(&mut self, device_id: Option<i32>, profile: Option<RemapProfile>) {
+        match (device_id, profile) {
+            (None, profile) => self.default_profile = profile,
+            (Some(device_id), Some(profile)) => {
+                self.device_profiles.insert(device_id, profile);
+            }
+            (Some(device_id), None) => {
+                self.device_profiles.remove(&device_id);
+            }
+        }
+    }
+
+    fn profile(&self, device_id: i32) -> Option<&RemapProfile> {
+        self.device_profiles
+            .get(&device_id)
+            .or(self.default_profile.as_ref())
+    }
+
+    pub fn map(&self, key: Keycode, device_id: i32) -> Vec<Keycode> {
+        match self.profile(device_id) {
+            Some(profile) => profile.map(key),
+            None => vec![key],
+        }
+    }
+
+    pub fn mode(&self, key: Keycode, device_id: i32) -> BindingMode {
+        self.profile(device_id)
+            .map_or(BindingMode::Normal, |profile| profile.mode(key))
+    }
+
+    pub fn hotkeys(&self, key: Keycode, device_id: i32) -> &[Hotkey] {
+        self.profile(device_id)
+            .map_or(&[], |profile| profile.hotkeys(key))
+    }
+}
+
+pub struct InputDispatcher {
+    touch: TouchTranslator,
+    pressed_buttons: i32,
+    pub remapper: KeyRemapper,
+    repeater: KeyRepeater,
+    pub navigator: FocusNavigator,
+    left_stick: AnalogStick,
+    right_stick: AnalogStick,
+    hat: AnalogStick,
+    /// Clicks at the virtual cursor while a stick is in mouse mode.
+    pub stick_click_key: Option<Keycode>,
+    last_poll: Option<Instant>,
+    composer: TextComposer,
+    /// Set in the deterministic mode, so gestures and repeats run on movie time.
+    virtual_now: Option<Instant>,
+    hotkeys: Vec<Hotkey>,
+}
 
-pub struct InputDispatcher;
 impl InputDispatcher {
+    pub fn new(
+        touch_config: TouchConfig,
+        left_stick: StickConfig,
+        right_stick: StickConfig,
+        repeat_config: RepeatConfig,
+    ) -> Self {
+        Self {
+            touch: TouchTranslator::new(touch_config),
+            pressed_buttons: 0,
+            remapper: KeyRemapper::default(),
+            repeater: KeyRepeater::new(repeat_config),
+            navigator: FocusNavigator::default(),
+            left_stick: AnalogStick::new(left_stick),
+            right_stick: AnalogStick::new(right_stick),
+            // Many controllers report their d-pad as a hat instead of key presses.
+            hat: AnalogStick::new(StickConfig {
+                mode: StickMode::Keys,
+                deadzone: 0.5,
+                eight_way: true,
+                ..Default::default()
+            }),
+            stick_click_key: None,
+            last_poll: None,
+            composer: TextComposer::default(),
+            virtual_now: None,
+            hotkeys: Vec::new(),
+        }
+    }
 
-    pub fn dispatch_touch_event<'a>(event: TouchEvent, player: &mut MutexGuard<'a, Player>) {
-        let current_action = KeyAction::from(POINTER_ACTION.load(atomic::Ordering::Relaxed));
-        if current_action != event.action {
-            if event.action == KeyAction::Down {
-                player.handle_event(PlayerEvent::MouseDown {
+    pub fn set_virtual_time(&mut self, now: Instant) {
+        self.virtual_now = Some(now);
+    }
+
+    fn now(&self) -> Instant {
+        self.virtual_now.unwrap_or_else(Instant::now)
+    }
+
+    pub fn dispatch<'a>(&mut self, event: InputEvent, player: &mut MutexGuard<'a, Player>) {
+        match event {
+            InputEvent::Key(event) => self.dispatch_key_event(event, player),
+            InputEvent::Touch(event) => self.dispatch_touch_event(event, player),
+            InputEvent::Pointer(event) => self.dispatch_pointer_event(event, player),
+            InputEvent::Joystick(event) => self.dispatch_joystick_event(event, player),
+            InputEvent::Text(event) => self.dispatch_text_event(event, player),
+        }
+    }
+
+    pub fn dispatch_joystick_event<'a>(&mut self, event: JoystickEvent, player: &mut MutexGuard<'a, Player>) {
+        let mut transitions = self.left_stick.update(event.left.0, event.left.1);
+        transitions.extend(self.right_stick.update(event.right.0, event.right.1));
+        transitions.extend(self.hat.update(event.hat.0, event.hat.1));
+        for (key, action) in transitions {
+            self.dispatch_key_event(KeyEvent::new(key, action, event.device_id), player);
+        }
+    }
+
+    fn stick_mouse_enabled(&self) -> bool {
+        self.left_stick.config.mode == StickMode::Mouse
+            || self.right_stick.config.mode == StickMode::Mouse
+    }
+
+    pub fn dispatch_touch_event<'a>(&mut self, event: TouchEvent, player: &mut MutexGuard<'a, Player>) {
+        let viewport = player.viewport_dimensions();
+        let bounds = (viewport.width as f64, viewport.height as f64);
+        if event.action == TouchAction::Down {
+            self.navigator.disengage();
+        }
+        for player_event in self.touch.on_touch(&event, self.now(), bounds) {
+            player.handle_event(player_event);
+        }
+    }
+
+    /// Runs time based gestures, repeats held keys and moves the stick cursor.
+    /// Called on every iteration of the worker loop.
+    pub fn poll<'a>(&mut self, player: &mut MutexGuard<'a, Player>) {
+        let now = self.now();
+        for player_event in self.touch.poll(now) {
+            player.handle_event(player_event);
+        }
+        send_key_transitions(self.repeater.poll(now), player);
+        let dt = self
+            .last_poll
+            .replace(now)
+            .map_or(0.0, |last| now.duration_since(last).as_secs_f64());
+        let left = self.left_stick.cursor_velocity();
+        let right = self.right_stick.cursor_velocity();
+        let (dx, dy) = ((left.0 + right.0) * dt, (left.1 + right.1) * dt);
+        if dx != 0.0 || dy != 0.0 {
+            let viewport = player.viewport_dimensions();
+            let bounds = (viewport.width as f64, viewport.height as f64);
+            let (x, y) = self.touch.move_cursor(dx, dy, bounds);
+            player.handle_event(PlayerEvent::MouseMove { x, y });
+        }
+    }
+
+    /// Hotkeys pressed since the last call, for the worker loop to act on.
+    pub fn take_hotkeys(&mut self) -> Vec<Hotkey> {
+        std::mem::take(&mut self.hotkeys)
+    }
+
+    /// Where the host should draw the virtual cursor, if it moved.
+    pub fn take_cursor_update(&mut self) -> Option<(f64, f64)> {
+        self.touch.take_cursor_update()
+    }
+
+    pub fn dispatch_pointer_event<'a>(&mut self, event: PointerEvent, player: &mut MutexGuard<'a, Player>) {
+        for player_event in self.translate_pointer_event(event) {
+            player.handle_event(player_event);
+        }
+    }
+
+    /// Presses and releases the buttons that changed since the previous pointer event.
+    fn translate_pointer_event(&mut self, event: PointerEvent) -> Vec<PlayerEvent> {
+        match event.action {
+            PointerAction::Scroll => {
+                return vec![PlayerEvent::MouseWheel {
+                    delta: MouseWheelDelta::Lines(event.scroll),
+                }];
+            }
+            // Android also sends this when a button goes down mid-hover.
+            PointerAction::HoverExit if self.pressed_buttons == 0 => return vec![PlayerEvent::MouseLeave],
+            PointerAction::Other => return Vec::new(),
+            PointerAction::Down => self.navigator.disengage(),
+            _ => (),
+        }
+        let mut events = vec![PlayerEvent::MouseMove {
+            x: event.x,
+            y: event.y,
+        }];
+        let buttons = event.effective_buttons();
+        for (mask, button) in POINTER_BUTTONS {
+            let was_pressed = self.pressed_buttons & mask != 0;
+            let is_pressed = buttons & mask != 0;
+            if !was_pressed && is_pressed {
+                events.push(PlayerEvent::MouseDown {
                     x: event.x,
                     y: event.y,
-                    button: MouseButton::Left,
+                    button,
                     index: None,
                 });
-                POINTER_ACTION.store(true, atomic::Ordering::Relaxed);
-            } else {
-                player.handle_event(PlayerEvent::MouseUp {
+            } else if was_pressed && !is_pressed {
+                events.push(PlayerEvent::MouseUp {
                     x: event.x,
                     y: event.y,
-                    button: MouseButton::Left,
+                    button,
                 });
-                POINTER_ACTION.store(false, atomic::Ordering::Relaxed);
             }
-        } else if event.action == KeyAction::Down {
-            player.handle_event(PlayerEvent::MouseMove {
-                x: event.x,
-                y: event.y,
-            });
+        }
+        self.pressed_buttons = buttons;
+        events
+    }
+
+    pub fn dispatch_text_event<'a>(&mut self, event: TextEvent, player: &mut MutexGuard<'a, Player>) {
+        for player_event in self.composer.on_text_event(event) {
+            player.handle_event(player_event);
+        }
+    }
+
+    /// Only collects the hotkeys an input presses, for live input while a replay drives the movie.
+    pub fn dispatch_hotkeys(&mut self, event: &InputEvent) {
+        if let InputEvent::Key(event) = event {
+            self.collect_hotkeys(event);
         }
     }
 
-    pub fn dispatch_key_event<'a>(event: KeyEvent, player: &mut MutexGuard<'a, Player>) {
-        if let Some(descriptor) = KEYCODE_DESCRIPTORS.get(&event.key.into()) {
+    fn collect_hotkeys(&mut self, event: &KeyEvent) {
+        if event.action == KeyAction::Down && !event.is_repeat() {
+            self.hotkeys
+                .extend_from_slice(self.remapper.hotkeys(event.key, event.device_id));
+        }
+    }
+
+    pub fn dispatch_key_event<'a>(&mut self, event: KeyEvent, player: &mut MutexGuard<'a, Player>) {
+        self.collect_hotkeys(&event);
+        let targets = self.remapper.map(event.key, event.device_id);
+        let remapped = targets != [event.key];
+        let source = (event.device_id, event.key.into());
+        if !remapped && !event.is_repeat() {
+            if let Some(action) = self.navigator.translate(event.key, event.action, ime::has_text_focus()) {
+                let transitions = match action {
+                    NavAction::Tap(key) => vec![(key, KeyAction::Down), (key, KeyAction::Up)],
+                    NavAction::Press(key, mode) => self.repeater.press(source, vec![key], mode, self.now()),
+                    NavAction::Release => self.repeater.release(source).unwrap_or_default(),
+                };
+                send_key_transitions(transitions, player);
+                return;
+            }
+        }
+        if event.is_repeat() {
+            // Games see a single press, only a focused text field gets the repeats.
+            if !remapped {
+                send_text(&event, player);
+            }
+            return;
+        }
+        if self.stick_click_key == Some(event.key) && self.stick_mouse_enabled() {
+            let (x, y) = self.touch.cursor_position();
             if event.action == KeyAction::Down {
-                player.handle_event(PlayerEvent::KeyDown { key: *descriptor });
+                player.handle_event(PlayerEvent::MouseDown { x, y, button: MouseButton::Left, index: None });
             } else {
-                player.handle_event(PlayerEvent::KeyUp { key: *descriptor });
+                player.handle_event(PlayerEvent::MouseUp { x, y, button: MouseButton::Left });
             }
+            return;
         }
+        if event.action == KeyAction::Down {
+            let descriptors = if !remapped {
+                // Not remapped, so the character the user's layout produced is the logical key.
+                let text = event.text();
+                let descriptor = match (key_descriptor(event.key, event.num_lock()), text) {
+                    (Some(descriptor), Some(c)) => Some(KeyDescriptor {
+                        logical_key: LogicalKey::Character(c),
+                        ..descriptor
+                    }),
+                    (Some(descriptor), None) => Some(descriptor),
+                    (None, Some(c)) => Some(KeyDescriptor {
+                        physical_key: PhysicalKey::Unknown,
+                        logical_key: LogicalKey::Character(c),
+                        key_location: KeyLocation::Standard,
+                    }),
+                    (None, None) => None,
+                };
+                descriptor.into_iter().collect()
+            } else {
+                targets
+                    .iter()
+                    .filter_map(|target| key_descriptor(*target, true))
+                    .collect::<Vec<_>>()
+            };
+            let mode = self.remapper.mode(event.key, event.device_id);
+            send_key_transitions(self.repeater.press(source, descriptors, mode, self.now()), player);
+            if !remapped {
+                send_text(&event, player);
+            }
+        } else {
+            // Released as whatever it was pressed as, even if the profile
+            // or the modifiers changed in between.
+            let transitions = self.repeater.release(source).unwrap_or_else(|| {
+                targets
+                    .iter()
+                    .filter_map(|target| key_descriptor(*target, true))
+                    .map(|descriptor| (descriptor, KeyAction::Up))
+                    .collect()
+            });
+            send_key_transitions(transitions, player);
+        }
+    }
+}
+
+fn send_key_transitions<'a>(transitions: Vec<(KeyDescriptor, KeyAction)>, player: &mut MutexGuard<'a, Player>) {
+    for (key, action) in transitions {
+        player.handle_event(match action {
+            KeyAction::Down => PlayerEvent::KeyDown { key },
+            KeyAction::Up => PlayerEvent::KeyUp { key },
+        });
     }
 }
 
-const KEYCODE_ARRAY: [Keycode; 108] = [
-    Keycode::Home,
-    Keycode::Keycode0,
-    Keycode::Keycode1,
-    Keycode::Keycode2,
-    Keycode::Keycode3,
-    Keycode::Keycode4,
-    Keycode::Keycode5,
-    Keycode::Keycode6,
-    Keycode::Keycode7,
-    Keycode::Keycode8,
-    Keycode::Keycode9,
-    Keycode::DpadUp,
-    Keycode::DpadDown,
-    Keycode::DpadLeft,
-    Keycode::DpadRight,
-    Keycode::A,
-    Keycode::B,
-    Keycode::C,
-    Keycode::D,
-    Keycode::E,
-    Keycode::F,
-    Keycode::G,
-    Keycode::H,
-    Keycode::I,
-    Keycode::J,
-    Keycode::K,
-    Keycode::L,
-    Keycode::M,
-    Keycode::N,
-    Keycode::O,
-    Keycode::P,
-    Keycode::Q,
-    Keycode::R,
-    Keycode::S,
-    Keycode::T,
-    Keycode::U,
-    Keycode::V,
-    Keycode::W,
-    Keycode::X,
-    Keycode::Y,
-    Keycode::Z,
-    Keycode::Comma,
-    Keycode::Period,
-    Keycode::AltLeft,
-    Keycode::AltRight,
-    Keycode::ShiftLeft,
-    Keycode::ShiftRight,
-    Keycode::Tab,
-    Keycode::Space,
-    Keycode::Enter,
-    Keycode::Del,
-    Keycode::Grave,
-    Keycode::Minus,
-    Keycode::Equals,
-    Keycode::LeftBracket,
-    Keycode::RightBracket,
-    Keycode::Backslash,
-    Keycode::Semicolon,
-    Keycode::Apostrophe,
-    Keycode::Slash,
-    Keycode::PageUp,
-    Keycode::PageDown,
-    Keycode::Escape,
-    Keycode::ForwardDel,
-    Keycode::CtrlLeft,
-    Keycode::CtrlRight,
-    Keycode::CapsLock,
-    Keycode::ScrollLock,
-    Keycode::MetaLeft,
-    Keycode::MetaRight,
-    Keycode::Sysrq,
-    Keycode::Break,
-    Keycode::MoveHome,
-    Keycode::MoveEnd,
-    Keycode::Insert,
-    Keycode::F1,
-    Keycode::F2,
-    Keycode::F3,
-    Keycode::F4,
-    Keycode::F5,
-    Keycode::F6,
-    Keycode::F7,
-    Keycode::F8,
-    Keycode::F9,
-    Keycode::F10,
-    Keycode::F11,
-    Keycode::F12,
-    Keycode::NumLock,
-    Keycode::Numpad0,
-    Keycode::Numpad1,
-    Keycode::Numpad2,
-    Keycode::Numpad3,
-    Keycode::Numpad4,
-    Keycode::Numpad5,
-    Keycode::Numpad6,
-    Keycode::Numpad7,
-    Keycode::Numpad8,
-    Keycode::Numpad9,
-    Keycode::NumpadDivide,
-    Keycode::NumpadMultiply,
-    Keycode::NumpadSubtract,
-    Keycode::NumpadAdd,
-    Keycode::NumpadDot,
-    Keycode::NumpadComma,
-    Keycode::NumpadEnter,
-    Keycode::NumpadEquals,
-    Keycode::MediaPlay,
-    Keycode::MediaPause,
+fn send_text<'a>(event: &KeyEvent, player: &mut MutexGuard<'a, Player>) {
+    if let Some(code) = text_control(event) {
+        player.handle_event(PlayerEvent::TextControl { code });
+    } else if let Some(codepoint) = event.text() {
+        player.handle_event(PlayerEvent::TextInput { codepoint });
+    }
+}
+
+/// Editing keys a focused text field expects as text controls rather than text.
+fn text_control(event: &KeyEvent) -> Option<TextControlCode> {
+    let shift = event.meta_state & META_SHIFT_ON != 0;
+    let ctrl = event.meta_state & META_CTRL_ON != 0;
+    match event.key {
+        // Ruffle reads and writes the clipboard through the UI backend for these.
+        Keycode::C if ctrl => Some(TextControlCode::Copy),
+        Keycode::V if ctrl => Some(TextControlCode::Paste),
+        Keycode::X if ctrl => Some(TextControlCode::Cut),
+        Keycode::A if ctrl => Some(TextControlCode::SelectAll),
+        Keycode::Copy => Some(TextControlCode::Copy),
+        Keycode::Paste => Some(TextControlCode::Paste),
+        Keycode::Cut => Some(TextControlCode::Cut),
+        Keycode::Del => Some(TextControlCode::Backspace),
+        Keycode::ForwardDel => Some(TextControlCode::Delete),
+        Keycode::Enter | Keycode::NumpadEnter | Keycode::DpadCenter => Some(TextControlCode::Enter),
+        Keycode::DpadLeft if shift => Some(TextControlCode::SelectLeft),
+        Keycode::DpadLeft => Some(TextControlCode::MoveLeft),
+        Keycode::DpadRight if shift => Some(TextControlCode::SelectRight),
+        Keycode::DpadRight => Some(TextControlCode::MoveRight),
+        _ => None,
+    }
+}
+
+const fn key(physical_key: PhysicalKey, logical_key: LogicalKey, key_location: KeyLocation) -> KeyDescriptor {
+    KeyDescriptor {
+        physical_key,
+        logical_key,
+        key_location,
+    }
+}
+
+const fn char_key(physical_key: PhysicalKey, c: char) -> KeyDescriptor {
+    key(physical_key, LogicalKey::Character(c), KeyLocation::Standard)
+}
+
+const fn named_key(physical_key: PhysicalKey, named: NamedKey) -> KeyDescriptor {
+    key(physical_key, LogicalKey::Named(named), KeyLocation::Standard)
+}
+
+/// Every Android keycode with a Flash equivalent. Keys that are left out,
+/// such as volume and system navigation, stay with the host.
+const KEY_MAPPINGS: [(Keycode, KeyDescriptor); 126] = [
+    (Keycode::Keycode0, char_key(PhysicalKey::Digit0, '0')),
+    (Keycode::Keycode1, char_key(PhysicalKey::Digit1, '1')),
+    (Keycode::Keycode2, char_key(PhysicalKey::Digit2, '2')),
+    (Keycode::Keycode3, char_key(PhysicalKey::Digit3, '3')),
+    (Keycode::Keycode4, char_key(PhysicalKey::Digit4, '4')),
+    (Keycode::Keycode5, char_key(PhysicalKey::Digit5, '5')),
+    (Keycode::Keycode6, char_key(PhysicalKey::Digit6, '6')),
+    (Keycode::Keycode7, char_key(PhysicalKey::Digit7, '7')),
+    (Keycode::Keycode8, char_key(PhysicalKey::Digit8, '8')),
+    (Keycode::Keycode9, char_key(PhysicalKey::Digit9, '9')),
+    (Keycode::A, char_key(PhysicalKey::KeyA, 'a')),
+    (Keycode::B, char_key(PhysicalKey::KeyB, 'b')),
+    (Keycode::C, char_key(PhysicalKey::KeyC, 'c')),
+    (Keycode::D, char_key(PhysicalKey::KeyD, 'd')),
+    (Keycode::E, char_key(PhysicalKey::KeyE, 'e')),
+    (Keycode::F, char_key(PhysicalKey::KeyF, 'f')),
+    (Keycode::G, char_key(PhysicalKey::KeyG, 'g')),
+    (Keycode::H, char_key(PhysicalKey::KeyH, 'h')),
+    (Keycode::I, char_key(PhysicalKey::KeyI, 'i')),
+    (Keycode::J, char_key(PhysicalKey::KeyJ, 'j')),
+    (Keycode::K, char_key(PhysicalKey::KeyK, 'k')),
+    (Keycode::L, char_key(PhysicalKey::KeyL, 'l')),
+    (Keycode::M, char_key(PhysicalKey::KeyM, 'm')),
+    (Keycode::N, char_key(PhysicalKey::KeyN, 'n')),
+    (Keycode::O, char_key(PhysicalKey::KeyO, 'o')),
+    (Keycode::P, char_key(PhysicalKey::KeyP, 'p')),
+    (Keycode::Q, char_key(PhysicalKey::KeyQ, 'q')),
+    (Keycode::R, char_key(PhysicalKey::KeyR, 'r')),
+    (Keycode::S, char_key(PhysicalKey::KeyS, 's')),
+    (Keycode::T, char_key(PhysicalKey::KeyT, 't')),
+    (Keycode::U, char_key(PhysicalKey::KeyU, 'u')),
+    (Keycode::V, char_key(PhysicalKey::KeyV, 'v')),
+    (Keycode::W, char_key(PhysicalKey::KeyW, 'w')),
+    (Keycode::X, char_key(PhysicalKey::KeyX, 'x')),
+    (Keycode::Y, char_key(PhysicalKey::KeyY, 'y')),
+    (Keycode::Z, char_key(PhysicalKey::KeyZ, 'z')),
+    (Keycode::Space, char_key(PhysicalKey::Space, ' ')),
+    (Keycode::Grave, char_key(PhysicalKey::Backquote, '`')),
+    (Keycode::Minus, char_key(PhysicalKey::Minus, '-')),
+    (Keycode::Equals, char_key(PhysicalKey::Equal, '=')),
+    (Keycode::LeftBracket, char_key(PhysicalKey::BracketLeft, '[')),
+    (Keycode::RightBracket, char_key(PhysicalKey::BracketRight, ']')),
+    (Keycode::Backslash, char_key(PhysicalKey::Backslash, '\\')),
+    (Keycode::Semicolon, char_key(PhysicalKey::Semicolon, ';')),
+    (Keycode::Apostrophe, char_key(PhysicalKey::Quote, '\'')),
+    (Keycode::Comma, char_key(PhysicalKey::Comma, ',')),
+    (Keycode::Period, char_key(PhysicalKey::Period, '.')),
+    (Keycode::Slash, char_key(PhysicalKey::Slash, '/')),
+    // Phone keypad symbols have no key of their own on a desktop keyboard.
+    (Keycode::Star, char_key(PhysicalKey::Unknown, '*')),
+    (Keycode::Pound, char_key(PhysicalKey::Unknown, '#')),
+    (Keycode::At, char_key(PhysicalKey::Unknown, '@')),
+    (Keycode::Plus, char_key(PhysicalKey::Unknown, '+')),
+    (Keycode::DpadUp, named_key(PhysicalKey::ArrowUp, NamedKey::ArrowUp)),
+    (Keycode::DpadDown, named_key(PhysicalKey::ArrowDown, NamedKey::ArrowDown)),
+    (Keycode::DpadLeft, named_key(PhysicalKey::ArrowLeft, NamedKey::ArrowLeft)),
+    (Keycode::DpadRight, named_key(PhysicalKey::ArrowRight, NamedKey::ArrowRight)),
+    // Remotes and gamepads confirm with the d-pad center, keyboards with Enter.
+    (Keycode::DpadCenter, named_key(PhysicalKey::Enter, NamedKey::Enter)),
+    (Keycode::Enter, named_key(PhysicalKey::Enter, NamedKey::Enter)),
+    (Keycode::Tab, named_key(PhysicalKey::Tab, NamedKey::Tab)),
+    (Keycode::Del, named_key(PhysicalKey::Backspace, NamedKey::Backspace)),
+    (Keycode::ForwardDel, named_key(PhysicalKey::Delete, NamedKey::Delete)),
+    (Keycode::Escape, named_key(PhysicalKey::Escape, NamedKey::Escape)),
+    (Keycode::Insert, named_key(PhysicalKey::Insert, NamedKey::Insert)),
+    (Keycode::MoveHome, named_key(PhysicalKey::Home, NamedKey::Home)),
+    (Keycode::MoveEnd, named_key(PhysicalKey::End, NamedKey::End)),
+    (Keycode::PageUp, named_key(PhysicalKey::PageUp, NamedKey::PageUp)),
+    (Keycode::PageDown, named_key(PhysicalKey::PageDown, NamedKey::PageDown)),
+    (Keycode::Clear, named_key(PhysicalKey::Unknown, NamedKey::Clear)),
+    (Keycode::Menu, named_key(PhysicalKey::ContextMenu, NamedKey::ContextMenu)),
+    (Keycode::Sysrq, named_key(PhysicalKey::PrintScreen, NamedKey::PrintScreen)),
+    (Keycode::Break, named_key(PhysicalKey::Pause, NamedKey::Pause)),
+    (Keycode::CapsLock, named_key(PhysicalKey::CapsLock, NamedKey::CapsLock)),
+    (Keycode::ScrollLock, named_key(PhysicalKey::ScrollLock, NamedKey::ScrollLock)),
+    (Keycode::NumLock, named_key(PhysicalKey::NumLock, NamedKey::NumLock)),
+    (Keycode::ShiftLeft, key(PhysicalKey::ShiftLeft, LogicalKey::Named(NamedKey::Shift), KeyLocation::Left)),
+    (Keycode::ShiftRight, key(PhysicalKey::ShiftRight, LogicalKey::Named(NamedKey::Shift), KeyLocation::Right)),
+    (Keycode::CtrlLeft, key(PhysicalKey::ControlLeft, LogicalKey::Named(NamedKey::Control), KeyLocation::Left)),
+    (Keycode::CtrlRight, key(PhysicalKey::ControlRight, LogicalKey::Named(NamedKey::Control), KeyLocation::Right)),
+    (Keycode::AltLeft, key(PhysicalKey::AltLeft, LogicalKey::Named(NamedKey::Alt), KeyLocation::Left)),
+    (Keycode::AltRight, key(PhysicalKey::AltRight, LogicalKey::Named(NamedKey::Alt), KeyLocation::Right)),
+    (Keycode::MetaLeft, key(PhysicalKey::MetaLeft, LogicalKey::Named(NamedKey::Meta), KeyLocation::Left)),
+    (Keycode::MetaRight, key(PhysicalKey::MetaRight, LogicalKey::Named(NamedKey::Meta), KeyLocation::Right)),
+    (Keycode::F1, named_key(PhysicalKey::F1, NamedKey::F1)),
+    (Keycode::F2, named_key(PhysicalKey::F2, NamedKey::F2)),
+    (Keycode::F3, named_key(PhysicalKey::F3, NamedKey::F3)),
+    (Keycode::F4, named_key(PhysicalKey::F4, NamedKey::F4)),
+    (Keycode::F5, named_key(PhysicalKey::F5, NamedKey::F5)),
+    (Keycode::F6, named_key(PhysicalKey::F6, NamedKey::F6)),
+    (Keycode::F7, named_key(PhysicalKey::F7, NamedKey::F7)),
+    (Keycode::F8, named_key(PhysicalKey::F8, NamedKey::F8)),
+    (Keycode::F9, named_key(PhysicalKey::F9, NamedKey::F9)),
+    (Keycode::F10, named_key(PhysicalKey::F10, NamedKey::F10)),
+    (Keycode::F11, named_key(PhysicalKey::F11, NamedKey::F11)),
+    (Keycode::F12, named_key(PhysicalKey::F12, NamedKey::F12)),
+    (Keycode::Numpad0, key(PhysicalKey::Numpad0, LogicalKey::Character('0'), KeyLocation::Numpad)),
+    (Keycode::Numpad1, key(PhysicalKey::Numpad1, LogicalKey::Character('1'), KeyLocation::Numpad)),
+    (Keycode::Numpad2, key(PhysicalKey::Numpad2, LogicalKey::Character('2'), KeyLocation::Numpad)),
+    (Keycode::Numpad3, key(PhysicalKey::Numpad3, LogicalKey::Character('3'), KeyLocation::Numpad)),
+    (Keycode::Numpad4, key(PhysicalKey::Numpad4, LogicalKey::Character('4'), KeyLocation::Numpad)),
+    (Keycode::Numpad5, key(PhysicalKey::Numpad5, LogicalKey::Character('5'), KeyLocation::Numpad)),
+    (Keycode::Numpad6, key(PhysicalKey::Numpad6, LogicalKey::Character('6'), KeyLocation::Numpad)),
+    (Keycode::Numpad7, key(PhysicalKey::Numpad7, LogicalKey::Character('7'), KeyLocation::Numpad)),
+    (Keycode::Numpad8, key(PhysicalKey::Numpad8, LogicalKey::Character('8'), KeyLocation::Numpad)),
+    (Keycode::Numpad9, key(PhysicalKey::Numpad9, LogicalKey::Character('9'), KeyLocation::Numpad)),
+    (Keycode::NumpadDot, key(PhysicalKey::NumpadDecimal, LogicalKey::Character('.'), KeyLocation::Numpad)),
+    (Keycode::NumpadComma, key(PhysicalKey::NumpadComma, LogicalKey::Character(','), KeyLocation::Numpad)),
+    (Keycode::NumpadDivide, key(PhysicalKey::NumpadDivide, LogicalKey::Character('/'), KeyLocation::Numpad)),
+    (Keycode::NumpadMultiply, key(PhysicalKey::NumpadMultiply, LogicalKey::Character('*'), KeyLocation::Numpad)),
+    (Keycode::NumpadSubtract, key(PhysicalKey::NumpadSubtract, LogicalKey::Character('-'), KeyLocation::Numpad)),
+    (Keycode::NumpadAdd, key(PhysicalKey::NumpadAdd, LogicalKey::Character('+'), KeyLocation::Numpad)),
+    (Keycode::NumpadEquals, key(PhysicalKey::Unknown, LogicalKey::Character('='), KeyLocation::Numpad)),
+    (Keycode::NumpadLeftParen, key(PhysicalKey::Unknown, LogicalKey::Character('('), KeyLocation::Numpad)),
+    (Keycode::NumpadRightParen, key(PhysicalKey::Unknown, LogicalKey::Character(')'), KeyLocation::Numpad)),
+    (Keycode::NumpadEnter, key(PhysicalKey::NumpadEnter, LogicalKey::Named(NamedKey::Enter), KeyLocation::Numpad)),
+    (Keycode::MediaPlay, named_key(PhysicalKey::Unknown, NamedKey::Play)),
+    (Keycode::MediaPause, named_key(PhysicalKey::Unknown, NamedKey::Pause)),
+    (Keycode::MediaPlayPause, named_key(PhysicalKey::Unknown, NamedKey::MediaPlayPause)),
+    (Keycode::MediaStop, named_key(PhysicalKey::Unknown, NamedKey::MediaStop)),
+    (Keycode::MediaNext, named_key(PhysicalKey::Unknown, NamedKey::MediaTrackNext)),
+    (Keycode::MediaPrevious, named_key(PhysicalKey::Unknown, NamedKey::MediaTrackPrevious)),
+    (Keycode::MediaRewind, named_key(PhysicalKey::Unknown, NamedKey::MediaRewind)),
+    (Keycode::MediaFastForward, named_key(PhysicalKey::Unknown, NamedKey::MediaFastForward)),
+    (Keycode::MediaRecord, named_key(PhysicalKey::Unknown, NamedKey::MediaRecord)),
+    (Keycode::Cut, named_key(PhysicalKey::Unknown, NamedKey::Cut)),
+    (Keycode::Copy, named_key(PhysicalKey::Unknown, NamedKey::Copy)),
+    (Keycode::Paste, named_key(PhysicalKey::Unknown, NamedKey::Paste)),
+];
+
+/// What the numpad keys do with NumLock off, as on a desktop keyboard.
+const NUMPAD_NAVIGATION: [(Keycode, NamedKey); 11] = [
+    (Keycode::Numpad0, NamedKey::Insert),
+    (Keycode::Numpad1, NamedKey::End),
+    (Keycode::Numpad2, NamedKey::ArrowDown),
+    (Keycode::Numpad3, NamedKey::PageDown),
+    (Keycode::Numpad4, NamedKey::ArrowLeft),
+    (Keycode::Numpad5, NamedKey::Clear),
+    (Keycode::Numpad6, NamedKey::ArrowRight),
+    (Keycode::Numpad7, NamedKey::Home),
+    (Keycode::Numpad8, NamedKey::ArrowUp),
+    (Keycode::Numpad9, NamedKey::PageUp),
+    (Keycode::NumpadDot, NamedKey::Delete),
 ];
 
-fn keycode_as_string(keycode: &Keycode) -> &str {
-    match keycode {
-        Keycode::Home => "Home",
-        Keycode::Keycode0 | Keycode::Numpad0 => "0",
-        Keycode::Keycode1 | Keycode::Numpad1 => "1",
-        Keycode::Keycode2 | Keycode::Numpad2 => "2",
-        Keycode::Keycode3 | Keycode::Numpad3 => "3",
-        Keycode::Keycode4 | Keycode::Numpad4 => "4",
-        Keycode::Keycode5 | Keycode::Numpad5 => "5",
-        Keycode::Keycode6 | Keycode::Numpad6 => "6",
-        Keycode::Keycode7 | Keycode::Numpad7 => "7",
-        Keycode::Keycode8 | Keycode::Numpad8 => "8",
-        Keycode::Keycode9 | Keycode::Numpad9 => "9",
-        Keycode::DpadUp => "ArrowUp",
-        Keycode::DpadDown => "ArrowDown",
-        Keycode::DpadLeft => "ArrowLeft",
-        Keycode::DpadRight => "ArrowRight",
-        Keycode::A => "A",
-        Keycode::B => "B",
-        Keycode::C => "C",
-        Keycode::D => "D",
-        Keycode::E => "E",
-        Keycode::F => "F",
-        Keycode::G => "G",
-        Keycode::H => "H",
-        Keycode::I => "I",
-        Keycode::J => "J",
-        Keycode::K => "K",
-        Keycode::L => "L",
-        Keycode::M => "M",
-        Keycode::N => "N",
-        Keycode::O => "O",
-        Keycode::P => "P",
-        Keycode::Q => "Q",
-        Keycode::R => "R",
-        Keycode::S => "S",
-        Keycode::T => "T",
-        Keycode::U => "U",
-        Keycode::V => "V",
-        Keycode::W => "W",
-        Keycode::X => "X",
-        Keycode::Y => "Y",
-        Keycode::Z => "Z",
-        Keycode::Comma | Keycode::NumpadComma => ",",
-        Keycode::Period | Keycode::NumpadDot => ".",
-        Keycode::AltLeft => "AltLeft",
-        Keycode::AltRight => "AltRight",
-        Keycode::ShiftLeft => "ShiftLeft",
-        Keycode::ShiftRight => "ShiftRight",
-        Keycode::Tab => "Tab",
-        Keycode::Space => "Space",
-        Keycode::Enter | Keycode::NumpadEnter => "Enter",
-        Keycode::Del => "Del",
-        Keycode::Grave => "`",
-        Keycode::Minus | Keycode::NumpadSubtract => "-",
-        Keycode::Equals | Keycode::NumpadEquals => "=",
-        Keycode::LeftBracket => "[",
-        Keycode::RightBracket => "]",
-        Keycode::Backslash => "\\",
-        Keycode::Semicolon => ";",
-        Keycode::Apostrophe => "\'",
-        Keycode::Slash | Keycode::NumpadDivide => "/",
-        Keycode::PageUp => "PgDn",
-        Keycode::PageDown => "PgUp",
-        Keycode::Escape => "Escape",
-        Keycode::ForwardDel => "Del",
-        Keycode::CtrlLeft => "CtrlLeft",
-        Keycode::CtrlRight => "CtrlRight",
-        Keycode::CapsLock => "CapsLock",
-        Keycode::ScrollLock => "ScrollLock",
-        Keycode::MetaLeft => "MetaLeft",
-        Keycode::MetaRight => "MetaRight",
-        Keycode::Sysrq => "PrtSc",
-        Keycode::Break => "Pause",
-        Keycode::MoveHome => "Home",
-        Keycode::MoveEnd => "End",
-        Keycode::Insert => "Insert",
-        Keycode::F1 => "F1",
-        Keycode::F2 => "F2",
-        Keycode::F3 => "F3",
-        Keycode::F4 => "F4",
-        Keycode::F5 => "F5",
-        Keycode::F6 => "F6",
-        Keycode::F7 => "F7",
-        Keycode::F8 => "F8",
-        Keycode::F9 => "F9",
-        Keycode::F10 => "F10",
-        Keycode::F11 => "F11",
-        Keycode::F12 => "F12",
-        Keycode::NumLock => "NumLock",
-        Keycode::NumpadMultiply => "*",
-        Keycode::NumpadAdd => "+",
-        Keycode::MediaPlay => "MediaPlay",
-        Keycode::MediaPause => "MediaPause",
-        _ => "Unknow",
-    }
-}
-
-fn keycode_as_descriptor(keycode: &Keycode) -> KeyDescriptor {
-    match keycode {
-        Keycode::Home => KeyDescriptor {
-            physical_key: PhysicalKey::Home,
-            logical_key: LogicalKey::Named(NamedKey::Home),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Keycode0 => KeyDescriptor {
-            physical_key: PhysicalKey::Digit0,
-            logical_key: LogicalKey::Character('0'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Keycode1 => KeyDescriptor {
-            physical_key: PhysicalKey::Digit1,
-            logical_key: LogicalKey::Character('1'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Keycode2 => KeyDescriptor {
-            physical_key: PhysicalKey::Digit2,
-            logical_key: LogicalKey::Character('2'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Keycode3 => KeyDescriptor {
-            physical_key: PhysicalKey::Digit3,
-            logical_key: LogicalKey::Character('3'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Keycode4 => KeyDescriptor {
-            physical_key: PhysicalKey::Digit4,
-            logical_key: LogicalKey::Character('4'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Keycode5 => KeyDescriptor {
-            physical_key: PhysicalKey::Digit5,
-            logical_key: LogicalKey::Character('5'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Keycode6 => KeyDescriptor {
-            physical_key: PhysicalKey::Digit6,
-            logical_key: LogicalKey::Character('6'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Keycode7 => KeyDescriptor {
-            physical_key: PhysicalKey::Digit7,
-            logical_key: LogicalKey::Character('7'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Keycode8 => KeyDescriptor {
-            physical_key: PhysicalKey::Digit8,
-            logical_key: LogicalKey::Character('8'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Keycode9 => KeyDescriptor {
-            physical_key: PhysicalKey::Digit9,
-            logical_key: LogicalKey::Character('9'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::DpadUp => KeyDescriptor {
-            physical_key: PhysicalKey::ArrowUp,
-            logical_key: LogicalKey::Named(NamedKey::ArrowUp),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::DpadDown => KeyDescriptor {
-            physical_key: PhysicalKey::ArrowDown,
-            logical_key: LogicalKey::Named(NamedKey::ArrowDown),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::DpadLeft => KeyDescriptor {
-            physical_key: PhysicalKey::ArrowLeft,
-            logical_key: LogicalKey::Named(NamedKey::ArrowLeft),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::DpadRight => KeyDescriptor {
-            physical_key: PhysicalKey::ArrowRight,
-            logical_key: LogicalKey::Named(NamedKey::ArrowRight),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::A => KeyDescriptor {
-            physical_key: PhysicalKey::KeyA,
-            logical_key: LogicalKey::Character('a'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::B => KeyDescriptor {
-            physical_key: PhysicalKey::KeyB,
-            logical_key: LogicalKey::Character('b'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::C => KeyDescriptor {
-            physical_key: PhysicalKey::KeyC,
-            logical_key: LogicalKey::Character('c'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::D => KeyDescriptor {
-            physical_key: PhysicalKey::KeyD,
-            logical_key: LogicalKey::Character('d'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::E => KeyDescriptor {
-            physical_key: PhysicalKey::KeyE,
-            logical_key: LogicalKey::Character('e'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::F => KeyDescriptor {
-            physical_key: PhysicalKey::KeyF,
-            logical_key: LogicalKey::Character('f'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::G => KeyDescriptor {
-            physical_key: PhysicalKey::KeyG,
-            logical_key: LogicalKey::Character('g'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::H => KeyDescriptor {
-            physical_key: PhysicalKey::KeyH,
-            logical_key: LogicalKey::Character('h'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::I => KeyDescriptor {
-            physical_key: PhysicalKey::KeyI,
-            logical_key: LogicalKey::Character('i'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::J => KeyDescriptor {
-            physical_key: PhysicalKey::KeyJ,
-            logical_key: LogicalKey::Character('j'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::K => KeyDescriptor {
-            physical_key: PhysicalKey::KeyK,
-            logical_key: LogicalKey::Character('k'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::L => KeyDescriptor {
-            physical_key: PhysicalKey::KeyL,
-            logical_key: LogicalKey::Character('l'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::M => KeyDescriptor {
-            physical_key: PhysicalKey::KeyM,
-            logical_key: LogicalKey::Character('m'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::N => KeyDescriptor {
-            physical_key: PhysicalKey::KeyN,
-            logical_key: LogicalKey::Character('n'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::O => KeyDescriptor {
-            physical_key: PhysicalKey::KeyO,
-            logical_key: LogicalKey::Character('o'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::P => KeyDescriptor {
-            physical_key: PhysicalKey::KeyP,
-            logical_key: LogicalKey::Character('p'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Q => KeyDescriptor {
-            physical_key: PhysicalKey::KeyQ,
-            logical_key: LogicalKey::Character('q'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::R => KeyDescriptor {
-            physical_key: PhysicalKey::KeyR,
-            logical_key: LogicalKey::Character('r'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::S => KeyDescriptor {
-            physical_key: PhysicalKey::KeyS,
-            logical_key: LogicalKey::Character('s'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::T => KeyDescriptor {
-            physical_key: PhysicalKey::KeyT,
-            logical_key: LogicalKey::Character('t'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::U => KeyDescriptor {
-            physical_key: PhysicalKey::KeyU,
-            logical_key: LogicalKey::Character('u'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::V => KeyDescriptor {
-            physical_key: PhysicalKey::KeyV,
-            logical_key: LogicalKey::Character('v'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::W => KeyDescriptor {
-            physical_key: PhysicalKey::KeyW,
-            logical_key: LogicalKey::Character('w'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::X => KeyDescriptor {
-            physical_key: PhysicalKey::KeyX,
-            logical_key: LogicalKey::Character('x'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Y => KeyDescriptor {
-            physical_key: PhysicalKey::KeyY,
-            logical_key: LogicalKey::Character('y'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Z => KeyDescriptor {
-            physical_key: PhysicalKey::KeyZ,
-            logical_key: LogicalKey::Character('z'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Comma => KeyDescriptor {
-            physical_key: PhysicalKey::Comma,
-            logical_key: LogicalKey::Character(','),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Period => KeyDescriptor {
-            physical_key: PhysicalKey::Period,
-            logical_key: LogicalKey::Character('.'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::AltLeft => KeyDescriptor {
-            physical_key: PhysicalKey::AltLeft,
-            logical_key: LogicalKey::Named(NamedKey::Alt),
-            key_location: KeyLocation::Left,
-        },
-        Keycode::AltRight => KeyDescriptor {
-            physical_key: PhysicalKey::AltRight,
-            logical_key: LogicalKey::Named(NamedKey::Alt),
-            key_location: KeyLocation::Right,
-        },
-        Keycode::ShiftLeft => KeyDescriptor {
-            physical_key: PhysicalKey::ShiftLeft,
-            logical_key: LogicalKey::Named(NamedKey::Shift),
-            key_location: KeyLocation::Left,
-        },
-        Keycode::ShiftRight => KeyDescriptor {
-            physical_key: PhysicalKey::ShiftRight,
-            logical_key: LogicalKey::Named(NamedKey::Shift),
-            key_location: KeyLocation::Right,
-        },
-        Keycode::Tab => KeyDescriptor {
-            physical_key: PhysicalKey::Tab,
-            logical_key: LogicalKey::Named(NamedKey::Tab),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Space => KeyDescriptor {
-            physical_key: PhysicalKey::Space,
-            logical_key: LogicalKey::Character(' '),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Enter => KeyDescriptor {
-            physical_key: PhysicalKey::Enter,
-            logical_key: LogicalKey::Named(NamedKey::Enter),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Del => KeyDescriptor {
-            physical_key: PhysicalKey::Delete,
-            logical_key: LogicalKey::Named(NamedKey::Delete),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Grave => KeyDescriptor {
-            physical_key: PhysicalKey::Unknown,
-            logical_key: LogicalKey::Character('`'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Minus => KeyDescriptor {
-            physical_key: PhysicalKey::Minus,
-            logical_key: LogicalKey::Character('-'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Equals => KeyDescriptor {
-            physical_key: PhysicalKey::Equal,
-            logical_key: LogicalKey::Character('='),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::LeftBracket => KeyDescriptor {
-            physical_key: PhysicalKey::BracketLeft,
-            logical_key: LogicalKey::Character('['),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::RightBracket => KeyDescriptor {
-            physical_key: PhysicalKey::BracketRight,
-            logical_key: LogicalKey::Character(']'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Backslash => KeyDescriptor {
-            physical_key: PhysicalKey::Backslash,
-            logical_key: LogicalKey::Character('\\'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Semicolon => KeyDescriptor {
-            physical_key: PhysicalKey::Semicolon,
-            logical_key: LogicalKey::Character(';'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Apostrophe => KeyDescriptor {
-            physical_key: PhysicalKey::Unknown,
-            logical_key: LogicalKey::Character('\''),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Slash => KeyDescriptor {
-            physical_key: PhysicalKey::Slash,
-            logical_key: LogicalKey::Character('/'),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::PageUp => KeyDescriptor {
-            physical_key: PhysicalKey::PageUp,
-            logical_key: LogicalKey::Named(NamedKey::PageUp),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::PageDown => KeyDescriptor {
-            physical_key: PhysicalKey::PageDown,
-            logical_key: LogicalKey::Named(NamedKey::PageDown),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Escape => KeyDescriptor {
-            physical_key: PhysicalKey::Escape,
-            logical_key: LogicalKey::Named(NamedKey::Escape),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::ForwardDel => KeyDescriptor {
-            physical_key: PhysicalKey::Delete,
-            logical_key: LogicalKey::Named(NamedKey::Delete),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::CtrlLeft => KeyDescriptor {
-            physical_key: PhysicalKey::ControlLeft,
-            logical_key: LogicalKey::Named(NamedKey::Control),
-            key_location: KeyLocation::Left,
-        },
-        Keycode::CtrlRight => KeyDescriptor {
-            physical_key: PhysicalKey::ControlRight,
-            logical_key: LogicalKey::Named(NamedKey::Control),
-            key_location: KeyLocation::Right,
-        },
-        Keycode::CapsLock => KeyDescriptor {
-            physical_key: PhysicalKey::CapsLock,
-            logical_key: LogicalKey::Named(NamedKey::CapsLock),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::ScrollLock => KeyDescriptor {
-            physical_key: PhysicalKey::ScrollLock,
-            logical_key: LogicalKey::Named(NamedKey::ScrollLock),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::MetaLeft => KeyDescriptor {
-            physical_key: PhysicalKey::ContextMenu,
-            logical_key: LogicalKey::Named(NamedKey::ContextMenu),
-            key_location: KeyLocation::Left,
-        },
-        Keycode::MetaRight => KeyDescriptor {
-            physical_key: PhysicalKey::ContextMenu,
-            logical_key: LogicalKey::Named(NamedKey::ContextMenu),
-            key_location: KeyLocation::Right,
-        },
-        Keycode::Sysrq => KeyDescriptor {
-            physical_key: PhysicalKey::PrintScreen,
-            logical_key: LogicalKey::Named(NamedKey::PrintScreen),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Break => KeyDescriptor {
-            physical_key: PhysicalKey::Pause,
-            logical_key: LogicalKey::Named(NamedKey::Pause),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::MoveHome => KeyDescriptor {
-            physical_key: PhysicalKey::Home,
-            logical_key: LogicalKey::Named(NamedKey::Home),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::MoveEnd => KeyDescriptor {
-            physical_key: PhysicalKey::End,
-            logical_key: LogicalKey::Named(NamedKey::End),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::Insert => KeyDescriptor {
-            physical_key: PhysicalKey::Insert,
-            logical_key: LogicalKey::Named(NamedKey::Insert),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::F1 => KeyDescriptor {
-            physical_key: PhysicalKey::F1,
-            logical_key: LogicalKey::Named(NamedKey::F1),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::F2 => KeyDescriptor {
-            physical_key: PhysicalKey::F2,
-            logical_key: LogicalKey::Named(NamedKey::F2),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::F3 => KeyDescriptor {
-            physical_key: PhysicalKey::F3,
-            logical_key: LogicalKey::Named(NamedKey::F3),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::F4 => KeyDescriptor {
-            physical_key: PhysicalKey::F4,
-            logical_key: LogicalKey::Named(NamedKey::F4),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::F5 => KeyDescriptor {
-            physical_key: PhysicalKey::F5,
-            logical_key: LogicalKey::Named(NamedKey::F5),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::F6 => KeyDescriptor {
-            physical_key: PhysicalKey::F6,
-            logical_key: LogicalKey::Named(NamedKey::F6),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::F7 => KeyDescriptor {
-            physical_key: PhysicalKey::F7,
-            logical_key: LogicalKey::Named(NamedKey::F7),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::F8 => KeyDescriptor {
-            physical_key: PhysicalKey::F8,
-            logical_key: LogicalKey::Named(NamedKey::F8),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::F9 => KeyDescriptor {
-            physical_key: PhysicalKey::F9,
-            logical_key: LogicalKey::Named(NamedKey::F9),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::F10 => KeyDescriptor {
-            physical_key: PhysicalKey::F10,
-            logical_key: LogicalKey::Named(NamedKey::F10),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::F11 => KeyDescriptor {
-            physical_key: PhysicalKey::F11,
-            logical_key: LogicalKey::Named(NamedKey::F11),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::F12 => KeyDescriptor {
-            physical_key: PhysicalKey::F12,
-            logical_key: LogicalKey::Named(NamedKey::F12),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::NumLock => KeyDescriptor {
-            physical_key: PhysicalKey::NumLock,
-            logical_key: LogicalKey::Named(NamedKey::NumLock),
-            key_location: KeyLocation::Numpad,
-        },
-        Keycode::Numpad0 => KeyDescriptor {
-            physical_key: PhysicalKey::Numpad0,
-            logical_key: LogicalKey::Character('0'),
-            key_location: KeyLocation::Numpad,
-        },
-        Keycode::Numpad1 => KeyDescriptor {
-            physical_key: PhysicalKey::Numpad1,
-            logical_key: LogicalKey::Character('1'),
-            key_location: KeyLocation::Numpad,
-        },
-        Keycode::Numpad2 => KeyDescriptor {
-            physical_key: PhysicalKey::Numpad2,
-            logical_key: LogicalKey::Character('2'),
-            key_location: KeyLocation::Numpad,
-        },
-        Keycode::Numpad3 => KeyDescriptor {
-            physical_key: PhysicalKey::Numpad3,
-            logical_key: LogicalKey::Character('3'),
-            key_location: KeyLocation::Numpad,
-        },
-        Keycode::Numpad4 => KeyDescriptor {
-            physical_key: PhysicalKey::Numpad4,
-            logical_key: LogicalKey::Character('4'),
-            key_location: KeyLocation::Numpad,
-        },
-        Keycode::Numpad5 => KeyDescriptor {
-            physical_key: PhysicalKey::Numpad5,
-            logical_key: LogicalKey::Character('5'),
-            key_location: KeyLocation::Numpad,
-        },
-        Keycode::Numpad6 => KeyDescriptor {
-            physical_key: PhysicalKey::Numpad6,
-            logical_key: LogicalKey::Character('6'),
-            key_location: KeyLocation::Numpad,
-        },
-        Keycode::Numpad7 => KeyDescriptor {
-            physical_key: PhysicalKey::Numpad7,
-            logical_key: LogicalKey::Character('7'),
-            key_location: KeyLocation::Numpad,
-        },
-        Keycode::Numpad8 => KeyDescriptor {
-            physical_key: PhysicalKey::Numpad8,
-            logical_key: LogicalKey::Character('8'),
-            key_location: KeyLocation::Numpad,
-        },
-        Keycode::Numpad9 => KeyDescriptor {
-            physical_key: PhysicalKey::Numpad9,
-            logical_key: LogicalKey::Character('9'),
-            key_location: KeyLocation::Numpad,
-        },
-        Keycode::NumpadDivide => KeyDescriptor {
-            physical_key: PhysicalKey::NumpadDivide,
-            logical_key: LogicalKey::Character('/'),
-            key_location: KeyLocation::Numpad,
-        },
-        Keycode::NumpadMultiply => KeyDescriptor {
-            physical_key: PhysicalKey::NumpadMultiply,
-            logical_key: LogicalKey::Character('*'),
-            key_location: KeyLocation::Numpad,
-        },
-        Keycode::NumpadSubtract => KeyDescriptor {
-            physical_key: PhysicalKey::NumpadSubtract,
-            logical_key: LogicalKey::Character('-'),
-            key_location: KeyLocation::Numpad,
-        },
-        Keycode::NumpadAdd => KeyDescriptor {
-            physical_key: PhysicalKey::NumpadAdd,
-            logical_key: LogicalKey::Character('+'),
-            key_location: KeyLocation::Numpad,
-        },
-        Keycode::NumpadDot => KeyDescriptor {
-            physical_key: PhysicalKey::Unknown,
-            logical_key: LogicalKey::Character('.'),
-            key_location: KeyLocation::Numpad,
-        },
-        Keycode::NumpadComma => KeyDescriptor {
-            physical_key: PhysicalKey::NumpadComma,
-            logical_key: LogicalKey::Character(','),
-            key_location: KeyLocation::Numpad,
-        },
-        Keycode::NumpadEnter => KeyDescriptor {
-            physical_key: PhysicalKey::NumpadEnter,
-            logical_key: LogicalKey::Named(NamedKey::Enter),
-            key_location: KeyLocation::Numpad,
-        },
-        Keycode::NumpadEquals => KeyDescriptor {
-            physical_key: PhysicalKey::Unknown,
-            logical_key: LogicalKey::Character('='),
-            key_location: KeyLocation::Numpad,
-        },
-        Keycode::MediaPlay => KeyDescriptor {
-            physical_key: PhysicalKey::Unknown,
-            logical_key: LogicalKey::Named(NamedKey::Play),
-            key_location: KeyLocation::Standard,
-        },
-        Keycode::MediaPause => KeyDescriptor {
-            physical_key: PhysicalKey::Unknown,
-            logical_key: LogicalKey::Named(NamedKey::Pause),
-            key_location: KeyLocation::Standard,
-        },
-        _ => KeyDescriptor {
-            physical_key: PhysicalKey::Unknown,
-            logical_key: LogicalKey::Unknown,
-            key_location: KeyLocation::Standard,
-        },
+static KEYCODE_DESCRIPTORS: Lazy<HashMap<i32, KeyDescriptor>> = Lazy::new(|| {
+    KEY_MAPPINGS
+        .iter()
+        .map(|(keycode, descriptor)| ((*keycode).into(), *descriptor))
+        .collect()
+});
+
+/// The Flash key for an Android keycode, `None` if the movie should not see it.
+pub fn key_descriptor(keycode: Keycode, num_lock: bool) -> Option<KeyDescriptor> {
+    let descriptor = *KEYCODE_DESCRIPTORS.get(&keycode.into())?;
+    if num_lock {
+        return Some(descriptor);
+    }
+    match NUMPAD_NAVIGATION.iter().find(|(numpad, _)| *numpad == keycode) {
+        // The physical key and location still say numpad, like browsers report it.
+        Some((_, named)) => Some(KeyDescriptor {
+            logical_key: LogicalKey::Named(*named),
+            ..descriptor
+        }),
+        None => Some(descriptor),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use KeyLocation::{Left, Numpad, Right, Standard};
+    use LogicalKey::{Character, Named};
+
+    #[test]
+    fn parses_remap_profile() {
+        let profile = RemapProfile::parse(
+            "ButtonA -> Space, ButtonA → Z\n# comment\nDpadUp = W # trailing comment\n",
+        )
+        .unwrap();
+        assert_eq!(profile.map(Keycode::ButtonA), vec![Keycode::Space, Keycode::Z]);
+        assert_eq!(profile.map(Keycode::DpadUp), vec![Keycode::W]);
+        assert_eq!(profile.map(Keycode::ButtonB), vec![Keycode::ButtonB]);
+    }
+
+    #[test]
+    fn parses_binding_modes() {
+        let profile = RemapProfile::parse("ButtonX -> Z [turbo], ButtonX -> X\nButtonL1 -> ShiftLeft [toggle]").unwrap();
+        assert_eq!(profile.map(Keycode::ButtonX), vec![Keycode::Z, Keycode::X]);
+        assert_eq!(profile.mode(Keycode::ButtonX), BindingMode::Turbo);
+        assert_eq!(profile.mode(Keycode::ButtonL1), BindingMode::Toggle);
+        assert_eq!(profile.mode(Keycode::ButtonA), BindingMode::Normal);
+    }
+
+    #[test]
+    fn parses_hotkey_bindings() {
+        let profile = RemapProfile::parse("ButtonL2 -> @fast_forward\nButtonR2 -> @frame_advance, ButtonR2 -> Space").unwrap();
+        assert!(profile.map(Keycode::ButtonL2).is_empty());
+        assert_eq!(profile.hotkeys(Keycode::ButtonL2), [Hotkey::FastForward]);
+        assert_eq!(profile.map(Keycode::ButtonR2), vec![Keycode::Space]);
+        assert_eq!(profile.hotkeys(Keycode::ButtonR2), [Hotkey::FrameAdvance]);
+        assert!(profile.hotkeys(Keycode::ButtonA).is_empty());
+    }
+
+    fn dispatcher() -> InputDispatcher {
+        InputDispatcher::new(
+            TouchConfig::default(),
+            StickConfig::default(),
+            StickConfig::default(),
+            RepeatConfig::default(),
+        )
+    }
+
+    #[test]
+    fn collects_hotkeys_without_a_player() {
+        let mut input = dispatcher();
+        input.remapper.set_profile(None, RemapProfile::parse("ButtonL2 -> @fast_forward").ok());
+        let press = |action| InputEvent::Key(KeyEvent::new(Keycode::ButtonL2, action, 3));
+        input.dispatch_hotkeys(&press(KeyAction::Down));
+        input.dispatch_hotkeys(&InputEvent::Key(
+            KeyEvent::new(Keycode::ButtonL2, KeyAction::Down, 3).with_repeat_count(1),
+        ));
+        input.dispatch_hotkeys(&press(KeyAction::Up));
+        input.dispatch_hotkeys(&InputEvent::Key(KeyEvent::new(Keycode::ButtonA, KeyAction::Down, 3)));
+        assert_eq!(input.take_hotkeys(), [Hotkey::FastForward]);
+    }
+
+    #[test]
+    fn presses_only_the_buttons_that_changed() {
+        let mut input = dispatcher();
+        let mut pointer = |action, buttons| {
+            input.translate_pointer_event(PointerEvent::new(action, 4.0, 8.0, buttons, 0.0))
+        };
+        let out = pointer(PointerAction::Down, BUTTON_PRIMARY);
+        assert!(matches!(
+            out[..],
+            [PlayerEvent::MouseMove { x: 4.0, y: 8.0 }, PlayerEvent::MouseDown { button: MouseButton::Left, .. }]
+        ));
+        let out = pointer(PointerAction::Move, BUTTON_PRIMARY | BUTTON_SECONDARY);
+        assert!(matches!(
+            out[..],
+            [PlayerEvent::MouseMove { .. }, PlayerEvent::MouseDown { button: MouseButton::Right, .. }]
+        ));
+        let out = pointer(PointerAction::Move, BUTTON_SECONDARY);
+        assert!(matches!(
+            out[..],
+            [PlayerEvent::MouseMove { .. }, PlayerEvent::MouseUp { button: MouseButton::Left, .. }]
+        ));
+        // A pressed button holds off the hover exit Android sends along with it.
+        assert!(matches!(pointer(PointerAction::HoverExit, BUTTON_SECONDARY)[..], [PlayerEvent::MouseMove { .. }]));
+        let out = pointer(PointerAction::Up, 0);
+        assert!(matches!(
+            out[..],
+            [PlayerEvent::MouseMove { .. }, PlayerEvent::MouseUp { button: MouseButton::Right, .. }]
+        ));
+        assert!(matches!(pointer(PointerAction::HoverExit, 0)[..], [PlayerEvent::MouseLeave]));
+    }
+
+    #[test]
+    fn stylus_tip_is_the_primary_button() {
+        let mut input = dispatcher();
+        let mut pointer = |action| input.translate_pointer_event(PointerEvent::new(action, 1.0, 2.0, 0, 0.0));
+        assert!(matches!(
+            pointer(PointerAction::Down)[..],
+            [PlayerEvent::MouseMove { .. }, PlayerEvent::MouseDown { button: MouseButton::Left, .. }]
+        ));
+        assert!(matches!(pointer(PointerAction::Move)[..], [PlayerEvent::MouseMove { x: 1.0, y: 2.0 }]));
+        assert!(matches!(
+            pointer(PointerAction::Up)[..],
+            [PlayerEvent::MouseMove { .. }, PlayerEvent::MouseUp { button: MouseButton::Left, .. }]
+        ));
+    }
+
+    #[test]
+    fn scrolls_by_lines_without_moving() {
+        let mut input = dispatcher();
+        let out = input.translate_pointer_event(PointerEvent::new(PointerAction::Scroll, 4.0, 8.0, 0, -1.5));
+        assert!(matches!(
+            out[..],
+            [PlayerEvent::MouseWheel { delta: MouseWheelDelta::Lines(lines) }] if lines == -1.5
+        ));
+    }
+
+    #[test]
+    fn rejects_bad_bindings() {
+        assert!(RemapProfile::parse("ButtonA Space").is_err());
+        assert!(RemapProfile::parse("ButtonA -> Spacebar").is_err());
+        assert!(RemapProfile::parse("ButtonA -> Space [mash]").is_err());
+        assert!(RemapProfile::parse("ButtonA -> Space turbo]").is_err());
+        assert!(RemapProfile::parse("ButtonA -> @rewind").is_err());
+    }
+
+    #[test]
+    fn key_text_follows_meta_state() {
+        let key = |meta_state, c: char| KeyEvent::new(Keycode::Keycode1, KeyAction::Down, 0).with_text(meta_state, c as u32);
+        assert_eq!(key(META_SHIFT_ON, '!').text(), Some('!'));
+        assert_eq!(key(META_SHIFT_ON | META_CTRL_ON, '!').text(), None);
+        // META_ALT_ON with META_ALT_LEFT_ON or META_ALT_RIGHT_ON (AltGr).
+        assert_eq!(key(0x2 | 0x10, '!').text(), None);
+        assert_eq!(key(0x2 | 0x20, '€').text(), Some('€'));
+        assert_eq!(key(0, '\n').text(), None);
+        assert_eq!(KeyEvent::new(Keycode::Keycode1, KeyAction::Down, 0).text(), None);
+    }
+
+    #[test]
+    fn ctrl_shortcuts_are_clipboard_controls() {
+        let key = |key, meta_state| text_control(&KeyEvent::new(key, KeyAction::Down, 0).with_text(meta_state, 0));
+        assert_eq!(key(Keycode::C, META_CTRL_ON), Some(TextControlCode::Copy));
+        assert_eq!(key(Keycode::V, META_CTRL_ON | META_SHIFT_ON), Some(TextControlCode::Paste));
+        assert_eq!(key(Keycode::A, META_CTRL_ON), Some(TextControlCode::SelectAll));
+        assert_eq!(key(Keycode::Paste, 0), Some(TextControlCode::Paste));
+        assert_eq!(key(Keycode::C, 0), None);
+    }
+
+    /// Keycodes the movie never sees: volume, system, TV remote, IME and gamepad keys
+    /// stay with the host, gamepad buttons only reach the movie through a remap profile.
+    const HOST_KEYS: [&str; 163] = [
+        "Unknown", "Home", "Back", "Call", "Endcall", "Power", "Camera", "Sym", "Explorer",
+        "Envelope", "Num", "Headsethook", "Focus", "Notification", "Search", "Mute", "Pictsymbols",
+        "SwitchCharset", "Function", "Forward", "Info", "ChannelUp", "ChannelDown", "ZoomIn",
+        "ZoomOut", "Window", "Guide", "Dvr", "Bookmark", "Captions", "Settings", "AppSwitch",
+        "LanguageSwitch", "MannerMode", "Contacts", "Calendar", "Music", "Calculator", "Assist",
+        "Sleep", "Wakeup", "Pairing", "LastChannel", "VoiceAssist", "Help", "AllApps", "Refresh",
+        "ThumbsUp", "ThumbsDown", "ProfileSwitch", "ZenkakuHankaku", "Eisu", "Muhenkan", "Henkan",
+        "KatakanaHiragana", "Yen", "Ro", "Kana", "SoftLeft", "SoftRight", "SoftSleep", "VolumeUp",
+        "VolumeDown", "VolumeMute", "BrightnessDown", "BrightnessUp", "ButtonA", "ButtonB",
+        "ButtonC", "ButtonX", "ButtonY", "ButtonZ", "ButtonL1", "ButtonR1", "ButtonL2", "ButtonR2",
+        "ButtonThumbl", "ButtonThumbr", "ButtonStart", "ButtonSelect", "ButtonMode", "Button1",
+        "Button2", "Button3", "Button4", "Button5", "Button6", "Button7", "Button8", "Button9",
+        "Button10", "Button11", "Button12", "Button13", "Button14", "Button15", "Button16",
+        "DpadUpLeft", "DpadDownLeft", "DpadUpRight", "DpadDownRight", "MediaClose", "MediaEject",
+        "MediaAudioTrack", "MediaTopMenu", "MediaSkipForward", "MediaSkipBackward",
+        "MediaStepForward", "MediaStepBackward", "Keycode3dMode", "Keycode11", "Keycode12", "Tv",
+        "TvPower", "TvInput", "StbPower", "StbInput", "AvrPower", "AvrInput", "ProgRed",
+        "ProgGreen", "ProgYellow", "ProgBlue", "TvDataService", "TvRadioService", "TvTeletext",
+        "TvNumberEntry", "TvTerrestrialAnalog", "TvTerrestrialDigital", "TvSatellite",
+        "TvSatelliteBs", "TvSatelliteCs", "TvSatelliteService", "TvNetwork", "TvAntennaCable",
+        "TvInputHdmi1", "TvInputHdmi2", "TvInputHdmi3", "TvInputHdmi4", "TvInputComposite1",
+        "TvInputComposite2", "TvInputComponent1", "TvInputComponent2", "TvInputVga1",
+        "TvAudioDescription", "TvAudioDescriptionMixUp", "TvAudioDescriptionMixDown", "TvZoomMode",
+        "TvContentsMenu", "TvMediaContextMenu", "TvTimerProgramming", "NavigatePrevious",
+        "NavigateNext", "NavigateIn", "NavigateOut", "StemPrimary", "Stem1", "Stem2", "Stem3",
+        "SystemNavigationUp", "SystemNavigationDown", "SystemNavigationLeft",
+        "SystemNavigationRight",
+    ];
+
+    /// What the movie sees for every keycode it gets, with NumLock on.
+    const MOVIE_KEYS: [(Keycode, PhysicalKey, LogicalKey, KeyLocation); 126] = [
+        (Keycode::Keycode0, PhysicalKey::Digit0, Character('0'), Standard),
+        (Keycode::Keycode1, PhysicalKey::Digit1, Character('1'), Standard),
+        (Keycode::Keycode2, PhysicalKey::Digit2, Character('2'), Standard),
+        (Keycode::Keycode3, PhysicalKey::Digit3, Character('3'), Standard),
+        (Keycode::Keycode4, PhysicalKey::Digit4, Character('4'), Standard),
+        (Keycode::Keycode5, PhysicalKey::Digit5, Character('5'), Standard),
+        (Keycode::Keycode6, PhysicalKey::Digit6, Character('6'), Standard),
+        (Keycode::Keycode7, PhysicalKey::Digit7, Character('7'), Standard),
+        (Keycode::Keycode8, PhysicalKey::Digit8, Character('8'), Standard),
+        (Keycode::Keycode9, PhysicalKey::Digit9, Character('9'), Standard),
+        (Keycode::A, PhysicalKey::KeyA, Character('a'), Standard),
+        (Keycode::B, PhysicalKey::KeyB, Character('b'), Standard),
+        (Keycode::C, PhysicalKey::KeyC, Character('c'), Standard),
+        (Keycode::D, PhysicalKey::KeyD, Character('d'), Standard),
+        (Keycode::E, PhysicalKey::KeyE, Character('e'), Standard),
+        (Keycode::F, PhysicalKey::KeyF, Character('f'), Standard),
+        (Keycode::G, PhysicalKey::KeyG, Character('g'), Standard),
+        (Keycode::H, PhysicalKey::KeyH, Character('h'), Standard),
+        (Keycode::I, PhysicalKey::KeyI, Character('i'), Standard),
+        (Keycode::J, PhysicalKey::KeyJ, Character('j'), Standard),
+        (Keycode::K, PhysicalKey::KeyK, Character('k'), Standard),
+        (Keycode::L, PhysicalKey::KeyL, Character('l'), Standard),
+        (Keycode::M, PhysicalKey::KeyM, Character('m'), Standard),
+        (Keycode::N, PhysicalKey::KeyN, Character('n'), Standard),
+        (Keycode::O, PhysicalKey::KeyO, Character('o'), Standard),
+        (Keycode::P, PhysicalKey::KeyP, Character('p'), Standard),
+        (Keycode::Q, PhysicalKey::KeyQ, Character('q'), Standard),
+        (Keycode::R, PhysicalKey::KeyR, Character('r'), Standard),
+        (Keycode::S, PhysicalKey::KeyS, Character('s'), Standard),
+        (Keycode::T, PhysicalKey::KeyT, Character('t'), Standard),
+        (Keycode::U, PhysicalKey::KeyU, Character('u'), Standard),
+        (Keycode::V, PhysicalKey::KeyV, Character('v'), Standard),
+        (Keycode::W, PhysicalKey::KeyW, Character('w'), Standard),
+        (Keycode::X, PhysicalKey::KeyX, Character('x'), Standard),
+        (Keycode::Y, PhysicalKey::KeyY, Character('y'), Standard),
+        (Keycode::Z, PhysicalKey::KeyZ, Character('z'), Standard),
+        (Keycode::Space, PhysicalKey::Space, Character(' '), Standard),
+        (Keycode::Grave, PhysicalKey::Backquote, Character('`'), Standard),
+        (Keycode::Minus, PhysicalKey::Minus, Character('-'), Standard),
+        (Keycode::Equals, PhysicalKey::Equal, Character('='), Standard),
+        (Keycode::LeftBracket, PhysicalKey::BracketLeft, Character('['), Standard),
+        (Keycode::RightBracket, PhysicalKey::BracketRight, Character(']'), Standard),
+        (Keycode::Backslash, PhysicalKey::Backslash, Character('\\'), Standard),
+        (Keycode::Semicolon, PhysicalKey::Semicolon, Character(';'), Standard),
+        (Keycode::Apostrophe, PhysicalKey::Quote, Character('\''), Standard),
+        (Keycode::Comma, PhysicalKey::Comma, Character(','), Standard),
+        (Keycode::Period, PhysicalKey::Period, Character('.'), Standard),
+        (Keycode::Slash, PhysicalKey::Slash, Character('/'), Standard),
+        (Keycode::Star, PhysicalKey::Unknown, Character('*'), Standard),
+        (Keycode::Pound, PhysicalKey::Unknown, Character('#'), Standard),
+        (Keycode::At, PhysicalKey::Unknown, Character('@'), Standard),
+        (Keycode::Plus, PhysicalKey::Unknown, Character('+'), Standard),
+        (Keycode::DpadUp, PhysicalKey::ArrowUp, Named(NamedKey::ArrowUp), Standard),
+        (Keycode::DpadDown, PhysicalKey::ArrowDown, Named(NamedKey::ArrowDown), Standard),
+        (Keycode::DpadLeft, PhysicalKey::ArrowLeft, Named(NamedKey::ArrowLeft), Standard),
+        (Keycode::DpadRight, PhysicalKey::ArrowRight, Named(NamedKey::ArrowRight), Standard),
+        (Keycode::DpadCenter, PhysicalKey::Enter, Named(NamedKey::Enter), Standard),
+        (Keycode::Enter, PhysicalKey::Enter, Named(NamedKey::Enter), Standard),
+        (Keycode::Tab, PhysicalKey::Tab, Named(NamedKey::Tab), Standard),
+        (Keycode::Del, PhysicalKey::Backspace, Named(NamedKey::Backspace), Standard),
+        (Keycode::ForwardDel, PhysicalKey::Delete, Named(NamedKey::Delete), Standard),
+        (Keycode::Escape, PhysicalKey::Escape, Named(NamedKey::Escape), Standard),
+        (Keycode::Insert, PhysicalKey::Insert, Named(NamedKey::Insert), Standard),
+        (Keycode::MoveHome, PhysicalKey::Home, Named(NamedKey::Home), Standard),
+        (Keycode::MoveEnd, PhysicalKey::End, Named(NamedKey::End), Standard),
+        (Keycode::PageUp, PhysicalKey::PageUp, Named(NamedKey::PageUp), Standard),
+        (Keycode::PageDown, PhysicalKey::PageDown, Named(NamedKey::PageDown), Standard),
+        (Keycode::Clear, PhysicalKey::Unknown, Named(NamedKey::Clear), Standard),
+        (Keycode::Menu, PhysicalKey::ContextMenu, Named(NamedKey::ContextMenu), Standard),
+        (Keycode::Sysrq, PhysicalKey::PrintScreen, Named(NamedKey::PrintScreen), Standard),
+        (Keycode::Break, PhysicalKey::Pause, Named(NamedKey::Pause), Standard),
+        (Keycode::CapsLock, PhysicalKey::CapsLock, Named(NamedKey::CapsLock), Standard),
+        (Keycode::ScrollLock, PhysicalKey::ScrollLock, Named(NamedKey::ScrollLock), Standard),
+        (Keycode::NumLock, PhysicalKey::NumLock, Named(NamedKey::NumLock), Standard),
+        (Keycode::ShiftLeft, PhysicalKey::ShiftLeft, Named(NamedKey::Shift), Left),
+        (Keycode::ShiftRight, PhysicalKey::ShiftRight, Named(NamedKey::Shift), Right),
+        (Keycode::CtrlLeft, PhysicalKey::ControlLeft, Named(NamedKey::Control), Left),
+        (Keycode::CtrlRight, PhysicalKey::ControlRight, Named(NamedKey::Control), Right),
+        (Keycode::AltLeft, PhysicalKey::AltLeft, Named(NamedKey::Alt), Left),
+        (Keycode::AltRight, PhysicalKey::AltRight, Named(NamedKey::Alt), Right),
+        (Keycode::MetaLeft, PhysicalKey::MetaLeft, Named(NamedKey::Meta), Left),
+        (Keycode::MetaRight, PhysicalKey::MetaRight, Named(NamedKey::Meta), Right),
+        (Keycode::F1, PhysicalKey::F1, Named(NamedKey::F1), Standard),
+        (Keycode::F2, PhysicalKey::F2, Named(NamedKey::F2), Standard),
+        (Keycode::F3, PhysicalKey::F3, Named(NamedKey::F3), Standard),
+        (Keycode::F4, PhysicalKey::F4, Named(NamedKey::F4), Standard),
+        (Keycode::F5, PhysicalKey::F5, Named(NamedKey::F5), Standard),
+        (Keycode::F6, PhysicalKey::F6, Named(NamedKey::F6), Standard),
+        (Keycode::F7, PhysicalKey::F7, Named(NamedKey::F7), Standard),
+        (Keycode::F8, PhysicalKey::F8, Named(NamedKey::F8), Standard),
+        (Keycode::F9, PhysicalKey::F9, Named(NamedKey::F9), Standard),
+        (Keycode::F10, PhysicalKey::F10, Named(NamedKey::F10), Standard),
+        (Keycode::F11, PhysicalKey::F11, Named(NamedKey::F11), Standard),
+        (Keycode::F12, PhysicalKey::F12, Named(NamedKey::F12), Standard),
+        (Keycode::Numpad0, PhysicalKey::Numpad0, Character('0'), Numpad),
+        (Keycode::Numpad1, PhysicalKey::Numpad1, Character('1'), Numpad),
+        (Keycode::Numpad2, PhysicalKey::Numpad2, Character('2'), Numpad),
+        (Keycode::Numpad3, PhysicalKey::Numpad3, Character('3'), Numpad),
+        (Keycode::Numpad4, PhysicalKey::Numpad4, Character('4'), Numpad),
+        (Keycode::Numpad5, PhysicalKey::Numpad5, Character('5'), Numpad),
+        (Keycode::Numpad6, PhysicalKey::Numpad6, Character('6'), Numpad),
+        (Keycode::Numpad7, PhysicalKey::Numpad7, Character('7'), Numpad),
+        (Keycode::Numpad8, PhysicalKey::Numpad8, Character('8'), Numpad),
+        (Keycode::Numpad9, PhysicalKey::Numpad9, Character('9'), Numpad),
+        (Keycode::NumpadDot, PhysicalKey::NumpadDecimal, Character('.'), Numpad),
+        (Keycode::NumpadComma, PhysicalKey::NumpadComma, Character(','), Numpad),
+        (Keycode::NumpadDivide, PhysicalKey::NumpadDivide, Character('/'), Numpad),
+        (Keycode::NumpadMultiply, PhysicalKey::NumpadMultiply, Character('*'), Numpad),
+        (Keycode::NumpadSubtract, PhysicalKey::NumpadSubtract, Character('-'), Numpad),
+        (Keycode::NumpadAdd, PhysicalKey::NumpadAdd, Character('+'), Numpad),
+        (Keycode::NumpadEquals, PhysicalKey::Unknown, Character('='), Numpad),
+        (Keycode::NumpadLeftParen, PhysicalKey::Unknown, Character('('), Numpad),
+        (Keycode::NumpadRightParen, PhysicalKey::Unknown, Character(')'), Numpad),
+        (Keycode::NumpadEnter, PhysicalKey::NumpadEnter, Named(NamedKey::Enter), Numpad),
+        (Keycode::MediaPlay, PhysicalKey::Unknown, Named(NamedKey::Play), Standard),
+        (Keycode::MediaPause, PhysicalKey::Unknown, Named(NamedKey::Pause), Standard),
+        (Keycode::MediaPlayPause, PhysicalKey::Unknown, Named(NamedKey::MediaPlayPause), Standard),
+        (Keycode::MediaStop, PhysicalKey::Unknown, Named(NamedKey::MediaStop), Standard),
+        (Keycode::MediaNext, PhysicalKey::Unknown, Named(NamedKey::MediaTrackNext), Standard),
+        (Keycode::MediaPrevious, PhysicalKey::Unknown, Named(NamedKey::MediaTrackPrevious), Standard),
+        (Keycode::MediaRewind, PhysicalKey::Unknown, Named(NamedKey::MediaRewind), Standard),
+        (Keycode::MediaFastForward, PhysicalKey::Unknown, Named(NamedKey::MediaFastForward), Standard),
+        (Keycode::MediaRecord, PhysicalKey::Unknown, Named(NamedKey::MediaRecord), Standard),
+        (Keycode::Cut, PhysicalKey::Unknown, Named(NamedKey::Cut), Standard),
+        (Keycode::Copy, PhysicalKey::Unknown, Named(NamedKey::Copy), Standard),
+        (Keycode::Paste, PhysicalKey::Unknown, Named(NamedKey::Paste), Standard),
+    ];
+
+    fn mapped(keycode: Keycode) -> (PhysicalKey, LogicalKey, KeyLocation) {
+        let descriptor = key_descriptor(keycode, true).unwrap();
+        (descriptor.physical_key, descriptor.logical_key, descriptor.key_location)
+    }
+
+    #[test]
+    fn every_keycode_is_mapped_or_left_to_the_host() {
+        for code in 0..=MAX_KEYCODE {
+            let keycode = Keycode::from(code);
+            let name = format!("{:?}", keycode);
+            if name.starts_with("__Unknown") {
+                continue;
+            }
+            let left_to_host = HOST_KEYS.contains(&name.as_str());
+            assert_eq!(
+                key_descriptor(keycode, true).is_none(),
+                left_to_host,
+                "{name} should {}be mapped",
+                if left_to_host { "not " } else { "" }
+            );
+            assert_eq!(
+                MOVIE_KEYS.iter().any(|(expected, ..)| *expected == keycode),
+                !left_to_host,
+                "{name} should be listed in exactly one of MOVIE_KEYS and HOST_KEYS"
+            );
+        }
+    }
+
+    #[test]
+    fn maps_every_key() {
+        for (keycode, physical, logical, location) in MOVIE_KEYS {
+            assert_eq!(mapped(keycode), (physical, logical, location), "{:?}", keycode);
+        }
+    }
+
+    #[test]
+    fn numpad_navigates_with_num_lock_off() {
+        let navigation = [
+            (Keycode::Numpad0, PhysicalKey::Numpad0, NamedKey::Insert),
+            (Keycode::Numpad1, PhysicalKey::Numpad1, NamedKey::End),
+            (Keycode::Numpad2, PhysicalKey::Numpad2, NamedKey::ArrowDown),
+            (Keycode::Numpad3, PhysicalKey::Numpad3, NamedKey::PageDown),
+            (Keycode::Numpad4, PhysicalKey::Numpad4, NamedKey::ArrowLeft),
+            (Keycode::Numpad5, PhysicalKey::Numpad5, NamedKey::Clear),
+            (Keycode::Numpad6, PhysicalKey::Numpad6, NamedKey::ArrowRight),
+            (Keycode::Numpad7, PhysicalKey::Numpad7, NamedKey::Home),
+            (Keycode::Numpad8, PhysicalKey::Numpad8, NamedKey::ArrowUp),
+            (Keycode::Numpad9, PhysicalKey::Numpad9, NamedKey::PageUp),
+            (Keycode::NumpadDot, PhysicalKey::NumpadDecimal, NamedKey::Delete),
+        ];
+        assert_eq!(navigation.len(), NUMPAD_NAVIGATION.len());
+        for (keycode, physical, named) in navigation {
+            let descriptor = key_descriptor(keycode, false).unwrap();
+            assert_eq!(descriptor.physical_key, physical);
+            assert_eq!(descriptor.logical_key, LogicalKey::Named(named));
+            assert_eq!(descriptor.key_location, KeyLocation::Numpad);
+        }
+        // Operators and keys outside the numpad ignore NumLock.
+        assert_eq!(key_descriptor(Keycode::NumpadAdd, false), key_descriptor(Keycode::NumpadAdd, true));
+        assert_eq!(key_descriptor(Keycode::DpadUp, false), key_descriptor(Keycode::DpadUp, true));
+    }
+
+    #[test]
+    fn device_profile_overrides_default() {
+        let mut remapper = KeyRemapper::default();
+        remapper.set_profile(None, Some(RemapProfile::parse("ButtonA -> Space").unwrap()));
+        remapper.set_profile(Some(7), Some(RemapProfile::parse("ButtonA -> Enter").unwrap()));
+        assert_eq!(remapper.map(Keycode::ButtonA, 1), vec![Keycode::Space]);
+        assert_eq!(remapper.map(Keycode::ButtonA, 7), vec![Keycode::Enter]);
+        remapper.set_profile(Some(7), None);
+        assert_eq!(remapper.map(Keycode::ButtonA, 7), vec![Keycode::Space]);
     }
 }
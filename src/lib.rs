@@ -1,25 +1,42 @@
 mod media;
+mod clock;
+mod external;
+mod filedialog;
+mod frameskip;
+mod fscommand;
+mod ime;
 mod input;
+mod lifecycle;
+mod navigation;
+mod navigator;
+mod overlay;
+mod repeat;
+mod replay;
+mod speed;
+mod stats;
+mod stick;
+mod touch;
+mod ui;
 mod util;
 use std::{
-    any::Any, os::raw::c_void, path::{Path, PathBuf}, ptr::NonNull, sync::{
-        mpsc::{self, Receiver, Sender}, Arc, Mutex
-    }, thread::{self, JoinHandle}, time::Instant
+    any::Any, fs::File, os::{fd::FromRawFd, raw::c_void}, rc::Rc, path::{Path, PathBuf}, ptr::NonNull, sync::{
+        mpsc::{self, Receiver, Sender}, atomic::{AtomicBool, AtomicI64, Ordering}, Arc, Mutex
+    }, thread::{self, JoinHandle}, time::{Duration, Instant}
 };
 
 use jni::{
-    JNIEnv, JavaVM, NativeMethod, objects::{JObject, JString, JValue}, sys::{JNI_TRUE, JNI_VERSION_1_6, jboolean, jfloat, jint},
+    JNIEnv, JavaVM, NativeMethod, objects::{JByteArray, JObject, JObjectArray, JString, JValue}, sys::{JNI_FALSE, JNI_TRUE, JNI_VERSION_1_6, jboolean, jfloat, jint, jlong, jobject},
 };
-use log::{LevelFilter, error, info};
-use ndk::{event::Keycode, native_window::NativeWindow};
+use log::{error, info, warn};
+use ndk::{audio::AudioError, event::Keycode, native_window::NativeWindow};
 use ndk_sys::ANativeWindow_fromSurface;
 use ruffle_core::{
-    backend::log::NullLogBackend, config::Letterbox, tag_utils::SwfMovie, Player, PlayerBuilder, ViewportDimensions
+    backend::log::NullLogBackend, config::Letterbox, external::Value as ExternalValue, tag_utils::SwfMovie, Player, PlayerBuilder, ViewportDimensions
 };
 use ruffle_frontend_utils::backends::storage::DiskStorageBackend;
+use tokio::{runtime, task::{self, LocalSet}};
 use ruffle_render_wgpu::{
     backend::WgpuRenderBackend,
-    target::SwapChainTarget,
     wgpu::{
         Backends, PowerPreference, SurfaceTargetUnsafe,
         rwh::{AndroidDisplayHandle, HasWindowHandle, RawDisplayHandle},
@@ -27,29 +44,76 @@ use ruffle_render_wgpu::{
 };
 
 use crate::{
-    input::{
-        InputDispatcher, KeyAction, KeyEvent, TouchEvent
-    }, media::AAudioAudioBackend, util::{JniUtils, Properties, TypedValue}
+    clock::FixedClock, external::{from_java, set_host_callback, to_java, AndroidExternalInterface}, filedialog::DialogAnswer, frameskip::{FrameSkipper, DEFAULT_MAX_SKIPS}, fscommand::{AndroidFsCommands, FsAction, FsCommand}, ime::{self, TextEvent}, input::{
+        keycode_from_name, InputDispatcher, InputEvent, KeyAction, KeyEvent, PointerAction, PointerEvent, RemapProfile, TouchAction, TouchEvent, TouchPointer
+    }, stick::{JoystickEvent, StickConfig, StickMode, AXIS_HAT_X, AXIS_HAT_Y, AXIS_RZ, AXIS_X, AXIS_Y, AXIS_Z}, touch::{TouchConfig, TouchMode}, navigator::{AndroidNavigatorBackend, OpenUrlMode}, overlay::{create_renderer, OverlayHandle, OverlayImage, OverlayTarget}, ui::{mirror_clipboard, AndroidUiBackend, JavaHost}, lifecycle::{Lifecycle, LifecycleState}, repeat::RepeatConfig, replay::{InputRecorder, InputReplay}, speed::SpeedControl, stats::{PerfStats, StatsSnapshot}, media::{AAudioSink, AudioSink, AvSync, MixerAudioBackend, NullSink, RecoveryOutcome, WavFileSink, DEFAULT_BUFFER_BURSTS}, util::{JniUtils, Properties, TypedValue}
 };
 
 enum RuffleEvent {
     AttachSurface(NativeWindow),
     AdjustSurfaceSize(i32, i32),
     DetachSurface,
-    HandleKeyEvent(KeyEvent),
-    HandleTouchEvent(TouchEvent),
+    HandleInput(InputEvent),
+    SetRemapProfile(Option<i32>, Option<RemapProfile>),
+    SetSpeed(f64),
+    CallExternal(String, Vec<ExternalValue>, i32),
+    FsCommand(String, String),
+    StepFrame,
+    AudioStreamError(AudioError),
+    AudioDeviceChanged,
+    SetAudioBufferBursts(i32),
     Kill,
 }
 
 const PROP_SCALED_DENSITY: &str = "ruffle_scale_factor";
 const PROP_SAVE_DIRECTORY: &str = "ruffle_save_directory";
+const PROP_AUDIO_BUFFER_BURSTS: &str = "ruffle_audio_buffer_bursts";
+const PROP_AUDIO_SINK: &str = "ruffle_audio_sink";
+const PROP_AUDIO_WAV_PATH: &str = "ruffle_audio_wav_path";
+const PROP_AV_SYNC: &str = "ruffle_av_sync";
+const PROP_AV_SYNC_OFFSET: &str = "ruffle_av_sync_offset_ms";
+const PROP_BACKGROUND_AUDIO: &str = "ruffle_background_audio";
+const PROP_TOUCH_MODE: &str = "ruffle_touch_mode";
+const PROP_TOUCH_DRAG_THRESHOLD: &str = "ruffle_touch_drag_threshold";
+const PROP_TOUCH_LONG_PRESS: &str = "ruffle_touch_long_press_ms";
+const PROP_TOUCH_DOUBLE_TAP: &str = "ruffle_touch_double_tap_ms";
+const PROP_TRACKPAD_SENSITIVITY: &str = "ruffle_trackpad_sensitivity";
+const PROP_REMAP_PROFILE: &str = "ruffle_remap_profile";
+const PROP_LEFT_STICK_MODE: &str = "ruffle_left_stick_mode";
+const PROP_RIGHT_STICK_MODE: &str = "ruffle_right_stick_mode";
+const PROP_STICK_DEADZONE: &str = "ruffle_stick_deadzone";
+const PROP_STICK_EIGHT_WAY: &str = "ruffle_stick_eight_way";
+const PROP_STICK_MOUSE_SPEED: &str = "ruffle_stick_mouse_speed";
+const PROP_STICK_CLICK_BUTTON: &str = "ruffle_stick_click_button";
+const PROP_KEY_REPEAT_DELAY: &str = "ruffle_key_repeat_delay_ms";
+const PROP_KEY_REPEAT_INTERVAL: &str = "ruffle_key_repeat_interval_ms";
+const PROP_TURBO_RATE: &str = "ruffle_turbo_rate";
+const PROP_DPAD_NAVIGATION: &str = "ruffle_dpad_navigation";
+const PROP_INPUT_RECORD_PATH: &str = "ruffle_input_record_path";
+const PROP_INPUT_REPLAY_PATH: &str = "ruffle_input_replay_path";
+const PROP_DETERMINISTIC: &str = "ruffle_deterministic";
+const PROP_FIXED_TIMESTEP: &str = "ruffle_fixed_timestep_ms";
+const PROP_SPEED: &str = "ruffle_speed";
+const PROP_FAST_FORWARD_SPEED: &str = "ruffle_fast_forward_speed";
+const PROP_FRAME_RATE: &str = "ruffle_frame_rate";
+const PROP_FRAME_SKIP: &str = "ruffle_frame_skip";
+const PROP_FRAME_SKIP_MAX: &str = "ruffle_frame_skip_max";
+const PROP_STATS_OVERLAY: &str = "ruffle_stats_overlay";
+const PROP_OPEN_URL_MODE: &str = "ruffle_open_url_mode";
+
+const AXIS_VSCROLL: jint = 9;
 
 static TX: Mutex<Option<Sender<RuffleEvent>>> = Mutex::new(None);
 static RX: Mutex<Option<Receiver<RuffleEvent>>> = Mutex::new(None);
 static THREAD_HANDLE: Mutex<Option<JoinHandle<()>>> = Mutex::new(None);
+/// Cleared under the `TX` lock once the worker stops, so `em_stop` never leaves a `Kill` for the next one.
+static WORKER_RUNNING: AtomicBool = AtomicBool::new(false);
 
 static PROPS: Mutex<Properties> = Mutex::new(Properties::new());
 
+static AUDIO_LATENCY_US: AtomicI64 = AtomicI64::new(-1);
+static STATS: Mutex<StatsSnapshot> = Mutex::new(StatsSnapshot::new());
+
 fn send_event(event: RuffleEvent) {
     TX.lock()
         .unwrap()
@@ -65,6 +129,104 @@ fn poll_event() -> Result<RuffleEvent, mpsc::TryRecvError> {
     RX.lock().unwrap().as_ref().unwrap().try_recv()
 }
 
+fn wait_event() -> Result<RuffleEvent, mpsc::TryRecvError> {
+    RX.lock()
+        .unwrap()
+        .as_ref()
+        .unwrap()
+        .recv()
+        .map_err(|_| mpsc::TryRecvError::Disconnected)
+}
+
+/// Everything the worker does before leaving its loop, for `em_stop` and quitting movies alike.
+fn stop_worker(recorder: &mut Option<InputRecorder>) {
+    filedialog::cancel_all();
+    if let Some(Err(e)) = recorder.as_mut().map(InputRecorder::flush) {
+        error!("Failed to finish input recording! {e}");
+    }
+    let _tx = TX.lock().unwrap();
+    WORKER_RUNNING.store(false, Ordering::Relaxed);
+}
+
+fn create_touch_config(props: &mut Properties) -> TouchConfig {
+    let def = TouchConfig::default();
+    TouchConfig {
+        mode: props
+            .s(PROP_TOUCH_MODE)
+            .map_or(def.mode, |mode| TouchMode::from(mode.as_str())),
+        drag_threshold: props.f(PROP_TOUCH_DRAG_THRESHOLD, def.drag_threshold as f32) as f64,
+        long_press: Duration::from_millis(
+            props.f(PROP_TOUCH_LONG_PRESS, def.long_press.as_millis() as f32) as u64,
+        ),
+        double_tap: Duration::from_millis(
+            props.f(PROP_TOUCH_DOUBLE_TAP, def.double_tap.as_millis() as f32) as u64,
+        ),
+        trackpad_sensitivity: props.f(PROP_TRACKPAD_SENSITIVITY, def.trackpad_sensitivity as f32) as f64,
+    }
+}
+
+fn create_stick_config(props: &mut Properties, mode_key: &str, def_mode: StickMode) -> StickConfig {
+    let def = StickConfig::default();
+    StickConfig {
+        mode: props
+            .s(mode_key)
+            .map_or(def_mode, |mode| StickMode::from(mode.as_str())),
+        deadzone: props.f(PROP_STICK_DEADZONE, def.deadzone),
+        eight_way: props.b(PROP_STICK_EIGHT_WAY, def.eight_way),
+        mouse_speed: props.f(PROP_STICK_MOUSE_SPEED, def.mouse_speed as f32) as f64,
+        mouse_acceleration: def.mouse_acceleration,
+    }
+}
+
+fn create_repeat_config(props: &mut Properties) -> RepeatConfig {
+    let def = RepeatConfig::default();
+    RepeatConfig {
+        delay: Duration::from_millis(
+            props.f(PROP_KEY_REPEAT_DELAY, def.delay.as_millis() as f32) as u64,
+        ),
+        interval: Duration::from_millis(
+            props.f(PROP_KEY_REPEAT_INTERVAL, def.interval.as_millis() as f32) as u64,
+        ),
+        turbo_rate: props.f(PROP_TURBO_RATE, def.turbo_rate as f32) as f64,
+    }
+}
+
+fn create_audio_sink(props: &mut Properties, buffer_bursts: i32) -> Box<dyn AudioSink> {
+    let kind = props
+        .s(PROP_AUDIO_SINK)
+        .map(String::as_str)
+        .unwrap_or("aaudio");
+    match kind {
+        "null" => return Box::new(NullSink::new()),
+        "wav" => {
+            let path = props
+                .s(PROP_AUDIO_WAV_PATH)
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from("ruffle_audio.wav"));
+            match WavFileSink::new(&path) {
+                Ok(sink) => return Box::new(sink),
+                Err(e) => error!("Failed to create wav file {}! {e}", path.display()),
+            }
+        }
+        _ => {
+            let listener = Arc::new(|err| send_event(RuffleEvent::AudioStreamError(err)));
+            match AAudioSink::new(buffer_bursts, listener) {
+                Ok(sink) => return Box::new(sink),
+                Err(e) => error!("Failed to open AAudio stream! {e}"),
+            }
+        }
+    }
+    info!("Falling back to null audio sink.");
+    Box::new(NullSink::new())
+}
+
+fn create_audio_backend(props: &mut Properties, buffer_bursts: i32) -> MixerAudioBackend {
+    MixerAudioBackend::new(create_audio_sink(props, buffer_bursts)).unwrap_or_else(|e| {
+        error!("Failed to open audio sink, falling back to null audio sink! {e}");
+        MixerAudioBackend::new(Box::new(NullSink::new())).expect("Null audio sink never fails to open")
+    })
+}
+
 fn em_attach_surface(env: JNIEnv, _thiz: JObject, _activity: JObject, sf: JObject) {
     unsafe {
         let window_ptr = ANativeWindow_fromSurface(env.get_raw(), sf.into_raw());
@@ -82,7 +244,15 @@ fn em_detach_surface(_env: JNIEnv, _thiz: JObject) {
 }
 
 fn em_stop(_env: JNIEnv, _thiz: JObject) {
-    send_event(RuffleEvent::Kill);
+    {
+        let tx = TX.lock().unwrap();
+        // A movie that quit has stopped the worker already.
+        if WORKER_RUNNING.load(Ordering::Relaxed) {
+            tx.as_ref().unwrap().send(RuffleEvent::Kill).unwrap_or_else(|err| {
+                error!("Event send failed. {err}");
+            });
+        }
+    }
     info!("Waiting main thread to exit...");
     let mut handle_guard = THREAD_HANDLE.lock().unwrap();
     if let Some(handle) = handle_guard.take() {
@@ -96,31 +266,107 @@ fn em_start(mut env: JNIEnv, thiz: JObject, path: JString) -> jboolean {
     let s_thiz = env
         .new_global_ref(thiz)
         .expect("Failed to global thiz ref!");
+    let host = JavaHost::new(env.get_java_vm().unwrap(), s_thiz.clone());
+    WORKER_RUNNING.store(true, Ordering::Relaxed);
     let handle = thread::spawn(move || {
         let mut player_ref: Option<Arc<Mutex<Player>>> = None;
         let mut prev_frame_time = Instant::now();
         let mut s_env = vm
             .attach_current_thread()
             .expect("Failed to attach env thread");
+        // Loaders and file dialogs run here, polled between ticks.
+        let runtime = runtime::Builder::new_current_thread()
+            .build()
+            .expect("Failed to create async runtime!");
+        let tasks = Rc::new(LocalSet::new());
+        // Stats drawn over each frame by the renderer.
+        let overlay = OverlayHandle::default();
 
         let mut prop_ref = PROPS
             .lock()
             .unwrap();
         let dpi_scale_factor = prop_ref
             .f(PROP_SCALED_DENSITY, 1.0);
+        let audio_buffer_bursts = prop_ref
+            .f(PROP_AUDIO_BUFFER_BURSTS, DEFAULT_BUFFER_BURSTS as f32);
+        let mut av_sync = AvSync::new(
+            prop_ref.b(PROP_AV_SYNC, false),
+            prop_ref.f(PROP_AV_SYNC_OFFSET, 0.0) as f64,
+        );
+        let mut lifecycle = Lifecycle::new(prop_ref.b(PROP_BACKGROUND_AUDIO, false));
+        let mut window_ref: Option<NativeWindow> = None;
+        let mut input = InputDispatcher::new(
+            create_touch_config(&mut prop_ref),
+            create_stick_config(&mut prop_ref, PROP_LEFT_STICK_MODE, StickMode::Keys),
+            create_stick_config(&mut prop_ref, PROP_RIGHT_STICK_MODE, StickMode::Off),
+            create_repeat_config(&mut prop_ref),
+        );
+        input.navigator.enabled = prop_ref.b(PROP_DPAD_NAVIGATION, false);
+        input.stick_click_key = prop_ref
+            .s(PROP_STICK_CLICK_BUTTON)
+            .and_then(|name| keycode_from_name(name).ok());
+        if let Some(source) = prop_ref.s(PROP_REMAP_PROFILE) {
+            match RemapProfile::load(source) {
+                Ok(profile) => input.remapper.set_profile(None, Some(profile)),
+                Err(e) => error!("Failed to load remap profile! {e}"),
+            }
+        }
+        // Ticks run so far and the movie time they added up to, what replays are timed by.
+        let mut frame: u64 = 0;
+        let mut movie_time_us: u64 = 0;
+        let mut replay = prop_ref.s(PROP_INPUT_REPLAY_PATH).and_then(|path| {
+            InputReplay::load(Path::new(path))
+                .inspect(|_| info!("Replaying input from {path}, live input is ignored."))
+                .inspect_err(|e| error!("Failed to load input replay {path}! {e}"))
+                .ok()
+        });
+        let mut replay_drifted = false;
+        let mut deterministic = prop_ref.b(PROP_DETERMINISTIC, false);
+        if deterministic && !cfg!(feature = "deterministic") {
+            error!("Built without the deterministic feature, running in real time instead.");
+            deterministic = false;
+        }
+        let mut fixed_clock: Option<FixedClock> = None;
+        let mut speed = SpeedControl::new(
+            prop_ref.f(PROP_SPEED, 1.0) as f64,
+            prop_ref.f(PROP_FAST_FORWARD_SPEED, 4.0) as f64,
+        );
+        // Zero or less keeps the frame rate the movie declares.
+        let frame_rate = prop_ref.f(PROP_FRAME_RATE, 0.0);
+        let frame_rate = (frame_rate > 0.0).then_some(frame_rate as f64);
+        let mut frame_skip = FrameSkipper::new(
+            prop_ref.b(PROP_FRAME_SKIP, false),
+            prop_ref.f(PROP_FRAME_SKIP_MAX, DEFAULT_MAX_SKIPS as f32) as u32,
+        );
+        let mut perf = PerfStats::default();
+        let stats_overlay = prop_ref.b(PROP_STATS_OVERLAY, false);
+        *STATS.lock().unwrap() = StatsSnapshot::new();
+        ime::set_text_focus(false);
+        let mut recorder = prop_ref.s(PROP_INPUT_RECORD_PATH).and_then(|path| {
+            InputRecorder::create(Path::new(path))
+                .inspect_err(|e| error!("Failed to create input recording {path}! {e}"))
+                .ok()
+        });
         loop {
-            match poll_event() {
+            let next_event = if lifecycle.should_tick() {
+                poll_event()
+            } else {
+                wait_event()
+            };
+            match next_event {
                 Ok(event) => match event {
                     RuffleEvent::AttachSurface(window) => unsafe {
+                        let window = window_ref.insert(window);
                         let vw: u32 = window.width() as u32;
                         let vh: u32 = window.height() as u32;
                         if let Some(player_mtx) = &player_ref {
                             let mut player = player_mtx.lock().unwrap();
                             let renderer = <dyn Any>::downcast_mut::<
-                                WgpuRenderBackend<SwapChainTarget>,
+                                WgpuRenderBackend<OverlayTarget>,
                             >(player.renderer_mut())
                             .unwrap();
-                            let _ = renderer.recreate_surface_unsafe(
+                            let result = overlay.recreate_surface(
+                                renderer.descriptors(),
                                 SurfaceTargetUnsafe::RawHandle {
                                     raw_display_handle: RawDisplayHandle::Android(
                                         AndroidDisplayHandle::new(),
@@ -129,7 +375,18 @@ fn em_start(mut env: JNIEnv, thiz: JObject, path: JString) -> jboolean {
                                 },
                                 (vw, vh),
                             );
-                            player.set_is_playing(true);
+                            if let Err(e) = result {
+                                error!("Failed to recreate surface! {e}");
+                            }
+                            if lifecycle.on_surface_attached() == LifecycleState::Suspended {
+                                // Starts audio output again too, if the movie was playing.
+                                player.set_is_playing(lifecycle.was_playing());
+                                if let Some(clock) = &mut fixed_clock {
+                                    clock.resume(Instant::now());
+                                }
+                                perf.on_resume();
+                            }
+                            prev_frame_time = Instant::now();
                         } else {
                             let movie_root_path = Path::new(&movie_path)
                                 .parent()
@@ -138,9 +395,10 @@ fn em_start(mut env: JNIEnv, thiz: JObject, path: JString) -> jboolean {
                                 .into_owned();
                             let stroage_path = prop_ref
                                 .s(PROP_SAVE_DIRECTORY)
-                                .unwrap_or(&movie_root_path);
+                                .cloned()
+                                .unwrap_or(movie_root_path);
                             let movie = SwfMovie::from_path(&movie_path, None).unwrap();
-                            let renderer = WgpuRenderBackend::for_window_unsafe(
+                            let renderer = runtime.block_on(create_renderer(
                                 SurfaceTargetUnsafe::RawHandle {
                                     raw_display_handle: RawDisplayHandle::Android(
                                         AndroidDisplayHandle::new(),
@@ -150,21 +408,34 @@ fn em_start(mut env: JNIEnv, thiz: JObject, path: JString) -> jboolean {
                                 (vw, vh),
                                 Backends::GL,
                                 PowerPreference::HighPerformance,
-                            )
+                                overlay.clone(),
+                            ))
                             .unwrap();
                             player_ref = Some(
                                 PlayerBuilder::new()
                                     .with_renderer(renderer)
                                     .with_movie(movie)
                                     .with_storage(Box::new(DiskStorageBackend::new(PathBuf::from(stroage_path))))
-                                    .with_audio(AAudioAudioBackend::new().unwrap())
+                                    .with_audio(create_audio_backend(&mut prop_ref, audio_buffer_bursts as i32))
                                     .with_log(NullLogBackend::new())
+                                    .with_ui(AndroidUiBackend::new(host.clone()))
+                                    .with_navigator(AndroidNavigatorBackend::new(
+                                        host.clone(),
+                                        prop_ref
+                                            .s(PROP_OPEN_URL_MODE)
+                                            .map_or(OpenUrlMode::Confirm, |mode| OpenUrlMode::from(mode.as_str())),
+                                        tasks.clone(),
+                                    ))
                                     .with_viewport_dimensions(
                                         vw,
                                         vh,
                                         dpi_scale_factor as f64,
                                     )
                                     .with_letterbox(Letterbox::On)
+                                    .with_frame_rate(frame_rate)
+                                    .with_fs_commands(Box::new(AndroidFsCommands::new(|command, args| {
+                                        send_event(RuffleEvent::FsCommand(command, args))
+                                    })))
                                     .with_avm2_optimizer_enabled(true)
                                     .build()
                             );
@@ -172,6 +443,19 @@ fn em_start(mut env: JNIEnv, thiz: JObject, path: JString) -> jboolean {
                             if let Some(player_mtx) = &player_ref {
                                 let mut player = player_mtx.lock()
                                     .unwrap();
+                                lifecycle.on_surface_attached();
+                                prev_frame_time = Instant::now();
+                                if deterministic {
+                                    let step_ms = match prop_ref.f(PROP_FIXED_TIMESTEP, 0.0) {
+                                        step_ms if step_ms > 0.0 => step_ms as f64,
+                                        _ => 1000.0 / player.frame_rate(),
+                                    };
+                                    let clock = FixedClock::new(Duration::from_secs_f64(step_ms / 1000.0), prev_frame_time);
+                                    input.set_virtual_time(clock.now());
+                                    info!("Deterministic mode, stepping {step_ms}ms per tick.");
+                                    fixed_clock = Some(clock);
+                                }
+                                player.add_external_interface(Box::new(AndroidExternalInterface::new(host.clone())));
                                 player.set_is_playing(true);
                                 let result = s_env.call_method(
                                 &s_thiz, 
@@ -199,50 +483,270 @@ fn em_start(mut env: JNIEnv, thiz: JObject, path: JString) -> jboolean {
                     }
                     RuffleEvent::DetachSurface => {
                         if let Some(player_mtx) = &player_ref {
-                            player_mtx
+                            let mut player = player_mtx
+                                .lock()
+                                .unwrap();
+                            let is_playing = player.is_playing();
+                            let state = lifecycle.on_surface_detached(is_playing);
+                            if state == LifecycleState::Suspended {
+                                // Pauses audio output too, stream sounds would keep mixing otherwise.
+                                player.set_is_playing(false);
+                            }
+                            info!("Surface detached, entering {state}.");
+                        }
+                        window_ref = None;
+                    }
+                    RuffleEvent::HandleInput(event) => {
+                        if let Some(player_mtx) = &player_ref {
+                            // Live input would throw a replay off its recorded course,
+                            // only its speed hotkeys still get through.
+                            if replay.is_some() {
+                                input.dispatch_hotkeys(&event);
+                            } else {
+                                let result = recorder
+                                    .as_mut()
+                                    .map(|writer| writer.record(frame, movie_time_us, &event));
+                                if let Some(Err(e)) = result {
+                                    error!("Failed to record input, recording stopped! {e}");
+                                    recorder = None;
+                                }
+                                let mut player = player_mtx
+                                    .lock()
+                                    .unwrap();
+                                input.dispatch(event, &mut player);
+                            }
+                        }
+                    }
+                    RuffleEvent::SetRemapProfile(device_id, profile) => {
+                        input.remapper.set_profile(device_id, profile);
+                    }
+                    RuffleEvent::SetSpeed(value) => {
+                        speed.set_speed(value);
+                    }
+                    RuffleEvent::StepFrame => {
+                        speed.step_frame();
+                    }
+                    RuffleEvent::CallExternal(name, args, id) => {
+                        let result = match &player_ref {
+                            Some(player_mtx) => player_mtx
                                 .lock()
                                 .unwrap()
-                                .set_is_playing(false);
+                                .call_internal_interface(&name, args),
+                            None => ExternalValue::Undefined,
+                        };
+                        let result = host.with_env(|env| {
+                            let result = to_java(env, &result)?;
+                            env.call_method(
+                                host.thiz(),
+                                "onExternalCallResult",
+                                "(ILjava/lang/Object;)V",
+                                &[JValue::from(id), JValue::Object(&result)],
+                            )
+                            .map(|_| ())
+                        });
+                        if let Err(e) = result {
+                            error!("Failed to return the result of movie callback {name}! {e}");
+                        }
+                    }
+                    RuffleEvent::FsCommand(command, args) => {
+                        info!("fscommand(\"{command}\", \"{args}\")");
+                        match FsCommand::parse(&command, &args).action() {
+                            FsAction::Stop => {
+                                stop_worker(&mut recorder);
+                                // The host closes its screen, its em_stop finds the worker gone.
+                                host.call_with_strs("onFsCommand", &[&command, &args]);
+                                break;
+                            }
+                            FsAction::SetFullscreen(is_full) => {
+                                host.call("onFullscreenRequested", "(Z)V", &[JValue::from(is_full)]);
+                            }
+                            FsAction::SetScaleMode(mode) => {
+                                if let Some(player_mtx) = &player_ref {
+                                    player_mtx.lock().unwrap().mutate_with_update_context(|context| {
+                                        let stage = context.stage;
+                                        stage.set_scale_mode(context, mode, false);
+                                    });
+                                }
+                            }
+                            FsAction::Forward => {
+                                host.call_with_strs("onFsCommand", &[&command, &args]);
+                            }
+                        }
+                    }
+                    RuffleEvent::AudioStreamError(err) => {
+                        if let Some(player_mtx) = &player_ref {
+                            let mut player = player_mtx
+                                .lock()
+                                .unwrap();
+                            let audio =
+                                <dyn Any>::downcast_mut::<MixerAudioBackend>(player.audio_mut()).unwrap();
+                            audio.on_stream_error(err);
                         }
                     }
-                    RuffleEvent::HandleKeyEvent(event) => {
+                    RuffleEvent::SetAudioBufferBursts(buffer_bursts) => {
                         if let Some(player_mtx) = &player_ref {
                             let mut player = player_mtx
                                 .lock()
                                 .unwrap();
-                            InputDispatcher::dispatch_key_event(event, &mut player);
+                            let audio =
+                                <dyn Any>::downcast_mut::<MixerAudioBackend>(player.audio_mut()).unwrap();
+                            audio.set_buffer_bursts(buffer_bursts);
                         }
                     }
-                    RuffleEvent::HandleTouchEvent(mut event) => {
+                    RuffleEvent::AudioDeviceChanged => {
                         if let Some(player_mtx) = &player_ref {
                             let mut player = player_mtx
                                 .lock()
                                 .unwrap();
-                            let dimension = player.viewport_dimensions();
-                            event.x *= dimension.width as f64;
-                            event.y *= dimension.height as f64;
-                            InputDispatcher::dispatch_touch_event(event, &mut player);
+                            let audio =
+                                <dyn Any>::downcast_mut::<MixerAudioBackend>(player.audio_mut()).unwrap();
+                            audio.retry();
                         }
                     }
-                    RuffleEvent::Kill => break,
+                    RuffleEvent::Kill => {
+                        stop_worker(&mut recorder);
+                        break;
+                    }
                 },
-                Err(e) => {
-                    dbg!(e);
+                Err(mpsc::TryRecvError::Empty) => (),
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    error!("Event channel disconnected, stopping the worker.");
+                    stop_worker(&mut recorder);
+                    break;
                 }
             }
+            if !lifecycle.should_tick() {
+                continue;
+            }
+            // Tasks lock the player themselves.
+            runtime.block_on(tasks.run_until(task::yield_now()));
             if let Some(player_mtx) = &player_ref {
                 let mut player = player_mtx.lock().unwrap();
                 let now = Instant::now();
                 let dt = now.duration_since(prev_frame_time).as_micros();
                 if dt > 0 {
                     prev_frame_time = now;
-                    player.tick(dt as f64 / 1000.0);
-                    if player.needs_render() {
-                        player.render();
-                    }
                     let audio =
-                        <dyn Any>::downcast_mut::<AAudioAudioBackend>(player.audio_mut()).unwrap();
-                    audio.keep_stream_valid();
+                        <dyn Any>::downcast_mut::<MixerAudioBackend>(player.audio_mut()).unwrap();
+                    let recovery = audio.poll(now);
+                    let latency = audio.latency();
+                    let underruns = audio.underruns();
+                    AUDIO_LATENCY_US.store(latency.map_or(-1, |it| it.as_micros() as i64), Ordering::Relaxed);
+                    for hotkey in input.take_hotkeys() {
+                        speed.on_hotkey(hotkey);
+                    }
+                    if let Some(volume) = speed.volume_change(player.volume()) {
+                        player.set_volume(volume);
+                    }
+                    // A fixed clock runs whole steps only, so it ignores A/V sync.
+                    let frame_times = if speed.is_stepping() {
+                        if let Some(clock) = &mut fixed_clock {
+                            clock.resume(now);
+                        }
+                        let step_ms = fixed_clock
+                            .as_ref()
+                            .map_or(1000.0 / player.frame_rate(), FixedClock::step_ms);
+                        vec![step_ms; speed.take_steps() as usize]
+                    } else {
+                        match &mut fixed_clock {
+                            Some(clock) => vec![clock.step_ms(); clock.due_steps(now, speed.speed()) as usize],
+                            None => vec![av_sync.adjust(dt as f64 / 1000.0, latency) * speed.speed()],
+                        }
+                    };
+                    let ticked = !frame_times.is_empty();
+                    let tick_start = Instant::now();
+                    for frame_time in frame_times {
+                        for record in replay.as_mut().map(|it| it.take_due(frame)).unwrap_or_default() {
+                            if !replay_drifted && record.time_us.abs_diff(movie_time_us) > 1000 {
+                                replay_drifted = true;
+                                warn!(
+                                    "Replay drifted at frame {frame}: recorded at {}us, now {movie_time_us}us.",
+                                    record.time_us
+                                );
+                            }
+                            input.dispatch(record.event, &mut player);
+                        }
+                        if replay.as_ref().is_some_and(InputReplay::is_finished) {
+                            info!("Input replay finished at frame {frame}.");
+                            replay = None;
+                        }
+                        if let Some(clock) = &fixed_clock {
+                            // getTimer counts from the start time, so keep it trailing the wall clock by the virtual time.
+                            let start_time = clock.start_time(Instant::now());
+                            player.mutate_with_update_context(|context| context.start_time = start_time);
+                        }
+                        // Inputs reach the file before the tick they feed, in case it crashes or gets killed.
+                        if let Some(Err(e)) = recorder.as_mut().map(InputRecorder::flush) {
+                            error!("Failed to record input, recording stopped! {e}");
+                            recorder = None;
+                        }
+                        player.tick(frame_time);
+                        <dyn Any>::downcast_mut::<MixerAudioBackend>(player.audio_mut())
+                            .unwrap()
+                            .on_tick(Duration::from_secs_f64(frame_time.max(0.0) / 1000.0));
+                        frame += 1;
+                        movie_time_us += (frame_time * 1000.0) as u64;
+                        if let Some(clock) = &mut fixed_clock {
+                            clock.advance();
+                            input.set_virtual_time(clock.now());
+                        }
+                        input.poll(&mut player);
+                    }
+                    let tick_time = tick_start.elapsed();
+                    if ticked {
+                        perf.on_tick(tick_time);
+                    }
+                    if lifecycle.should_render() && player.needs_render() {
+                        let budget = Duration::from_secs_f64(1.0 / player.frame_rate());
+                        if frame_skip.should_render(tick_time, budget) {
+                            let render_start = Instant::now();
+                            player.render();
+                            let render_time = render_start.elapsed();
+                            frame_skip.on_rendered(render_time);
+                            perf.on_render(render_time, render_start);
+                        }
+                    }
+                    if perf.publish_due(now) {
+                        let snapshot = perf.snapshot(frame_skip.rendered, frame_skip.skipped, underruns);
+                        *STATS.lock().unwrap() = snapshot;
+                        if stats_overlay {
+                            overlay.show(Some(OverlayImage::from_text(
+                                &snapshot.overlay_text(),
+                                dpi_scale_factor.round() as u32,
+                            )));
+                        }
+                    }
+                    if let Some((x, y)) = input.take_cursor_update() {
+                        let result = s_env.call_method(
+                            &s_thiz,
+                            "onVirtualCursorMoved",
+                            "(FF)V",
+                            &[JValue::from(x as f32), JValue::from(y as f32)]
+                        );
+                        if let Err(e) = result {
+                            error!("Failed to report cursor position! {e}");
+                        }
+                    }
+                    match recovery {
+                        Some(RecoveryOutcome::Recovered { device_id }) => {
+                            let result = s_env.call_method(
+                                &s_thiz,
+                                "onAudioRouteChanged",
+                                "(I)V",
+                                &[JValue::from(device_id)]
+                            );
+                            if let Err(e) = result {
+                                error!("Failed to report audio route change! {e}");
+                            }
+                        }
+                        Some(RecoveryOutcome::GaveUp) => {
+                            let result = s_env.call_method(&s_thiz, "onAudioFailed", "()V", &[]);
+                            if let Err(e) = result {
+                                error!("Failed to report audio failure! {e}");
+                            }
+                        }
+                        None => (),
+                    }
                 }
             }
         }
@@ -254,22 +758,109 @@ fn em_start(mut env: JNIEnv, thiz: JObject, path: JString) -> jboolean {
 fn em_set_prop(mut env: JNIEnv, _thiz: JObject, k: JString, prop: JObject) {
     let key = JniUtils::to_string(&mut env, k);
     match key.as_str() {
-        PROP_SCALED_DENSITY => {
+        PROP_SCALED_DENSITY
+        | PROP_AUDIO_BUFFER_BURSTS
+        | PROP_AV_SYNC_OFFSET
+        | PROP_TOUCH_DRAG_THRESHOLD
+        | PROP_TOUCH_LONG_PRESS
+        | PROP_TOUCH_DOUBLE_TAP
+        | PROP_TRACKPAD_SENSITIVITY
+        | PROP_STICK_DEADZONE
+        | PROP_STICK_MOUSE_SPEED
+        | PROP_KEY_REPEAT_DELAY
+        | PROP_KEY_REPEAT_INTERVAL
+        | PROP_TURBO_RATE
+        | PROP_FIXED_TIMESTEP
+        | PROP_SPEED
+        | PROP_FAST_FORWARD_SPEED
+        | PROP_FRAME_RATE
+        | PROP_FRAME_SKIP_MAX => {
             PROPS
                 .lock()
                 .unwrap()
                 .put(key.as_str(), TypedValue::F(JniUtils::as_float(&mut env, prop)));
         }
-        PROP_SAVE_DIRECTORY => {
+        PROP_SAVE_DIRECTORY
+        | PROP_AUDIO_SINK
+        | PROP_AUDIO_WAV_PATH
+        | PROP_TOUCH_MODE
+        | PROP_REMAP_PROFILE
+        | PROP_LEFT_STICK_MODE
+        | PROP_RIGHT_STICK_MODE
+        | PROP_STICK_CLICK_BUTTON
+        | PROP_INPUT_RECORD_PATH
+        | PROP_INPUT_REPLAY_PATH
+        | PROP_OPEN_URL_MODE => {
             PROPS
                 .lock()
                 .unwrap()
                 .put(key.as_str(), TypedValue::S(JniUtils::as_string(&mut env, prop)));
         }
+        PROP_AV_SYNC
+        | PROP_BACKGROUND_AUDIO
+        | PROP_STICK_EIGHT_WAY
+        | PROP_DPAD_NAVIGATION
+        | PROP_DETERMINISTIC
+        | PROP_FRAME_SKIP
+        | PROP_STATS_OVERLAY => {
+            PROPS
+                .lock()
+                .unwrap()
+                .put(key.as_str(), TypedValue::B(JniUtils::as_bool(&mut env, prop)));
+        }
         _ => (),
     }
 }
 
+/// Tunes output latency against underruns while playing, `ruffle_audio_buffer_bursts` sets the initial value.
+fn em_set_audio_buffer_bursts(_env: JNIEnv, _thiz: JObject, buffer_bursts: jint) {
+    send_event(RuffleEvent::SetAudioBufferBursts(buffer_bursts));
+}
+
+/// Output devices were added or removed, a good time to retry audio that gave up.
+fn em_audio_device_changed(_env: JNIEnv, _thiz: JObject) {
+    send_event(RuffleEvent::AudioDeviceChanged);
+}
+
+fn em_get_audio_latency_ms(_env: JNIEnv, _thiz: JObject) -> jfloat {
+    let latency = AUDIO_LATENCY_US.load(Ordering::Relaxed);
+    if latency < 0 {
+        -1.0
+    } else {
+        latency as jfloat / 1000.0
+    }
+}
+
+/// Rolling worker loop timings and counters as a `Bundle`, refreshed twice a second.
+fn em_get_stats(mut env: JNIEnv, _thiz: JObject) -> jobject {
+    let stats = *STATS.lock().unwrap();
+    let bundle = env.new_object("android/os/Bundle", "()V", &[])
+        .expect("Failed to create stats Bundle!");
+    let floats = [
+        ("tickMs", stats.tick_ms),
+        ("tickMsMax", stats.tick_ms_max),
+        ("renderMs", stats.render_ms),
+        ("renderMsMax", stats.render_ms_max),
+        ("fps", stats.fps),
+    ];
+    for (key, value) in floats {
+        let key = env.new_string(key).unwrap();
+        env.call_method(&bundle, "putFloat", "(Ljava/lang/String;F)V", &[JValue::Object(&key), JValue::from(value as f32)])
+            .expect("Failed to call Bundle::putFloat() method!");
+    }
+    let longs = [
+        ("framesRendered", stats.frames_rendered),
+        ("framesSkipped", stats.frames_skipped),
+        ("audioUnderruns", stats.audio_underruns),
+    ];
+    for (key, value) in longs {
+        let key = env.new_string(key).unwrap();
+        env.call_method(&bundle, "putLong", "(Ljava/lang/String;J)V", &[JValue::Object(&key), JValue::from(value as jlong)])
+            .expect("Failed to call Bundle::putLong() method!");
+    }
+    bundle.into_raw()
+}
+
 fn em_dispatch_key_event(mut env: JNIEnv, _thiz: JObject, event: JObject) -> jboolean {
     let key = env.call_method(&event, "getKeyCode", "()I", &[])
         .expect("Failed to call KeyEvent::getKeyCode() method!")
@@ -279,20 +870,219 @@ fn em_dispatch_key_event(mut env: JNIEnv, _thiz: JObject, event: JObject) -> jbo
         .expect("Failed to call KeyEvent::getAction() method!")
         .i()
         .expect("Failed to call KeyEvent::getAction() method!");
-    send_event(RuffleEvent::HandleKeyEvent(KeyEvent::new(Keycode::from(key), KeyAction::from(action))));
+    let device_id = env.call_method(&event, "getDeviceId", "()I", &[])
+        .expect("Failed to call KeyEvent::getDeviceId() method!")
+        .i()
+        .expect("Failed to call KeyEvent::getDeviceId() method!");
+    let meta_state = env.call_method(&event, "getMetaState", "()I", &[])
+        .expect("Failed to call KeyEvent::getMetaState() method!")
+        .i()
+        .expect("Failed to call KeyEvent::getMetaState() method!");
+    let unicode_char = env.call_method(&event, "getUnicodeChar", "()I", &[])
+        .expect("Failed to call KeyEvent::getUnicodeChar() method!")
+        .i()
+        .expect("Failed to call KeyEvent::getUnicodeChar() method!");
+    let repeat_count = env.call_method(&event, "getRepeatCount", "()I", &[])
+        .expect("Failed to call KeyEvent::getRepeatCount() method!")
+        .i()
+        .expect("Failed to call KeyEvent::getRepeatCount() method!");
+    let key_event = KeyEvent::new(Keycode::from(key), KeyAction::from(action), device_id)
+        .with_text(meta_state as u32, unicode_char as u32)
+        .with_repeat_count(repeat_count);
+    send_event(RuffleEvent::HandleInput(InputEvent::Key(key_event)));
+    JNI_TRUE
+}
+
+fn em_dispatch_joystick_event(mut env: JNIEnv, _thiz: JObject, event: JObject) -> jboolean {
+    let device_id = env.call_method(&event, "getDeviceId", "()I", &[])
+        .and_then(|it| it.i())
+        .expect("Failed to call MotionEvent::getDeviceId() method!");
+    let mut axis = |axis: jint| {
+        env.call_method(&event, "getAxisValue", "(I)F", &[JValue::from(axis)])
+            .and_then(|it| it.f())
+            .expect("Failed to call MotionEvent::getAxisValue() method!")
+    };
+    let event = JoystickEvent {
+        device_id,
+        left: (axis(AXIS_X), axis(AXIS_Y)),
+        right: (axis(AXIS_Z), axis(AXIS_RZ)),
+        hat: (axis(AXIS_HAT_X), axis(AXIS_HAT_Y)),
+    };
+    send_event(RuffleEvent::HandleInput(InputEvent::Joystick(event)));
+    JNI_TRUE
+}
+
+fn em_commit_text(mut env: JNIEnv, _thiz: JObject, text: JString) {
+    let text = JniUtils::to_string(&mut env, text);
+    send_event(RuffleEvent::HandleInput(InputEvent::Text(TextEvent::Commit(text))));
+}
+
+fn em_set_composing_text(mut env: JNIEnv, _thiz: JObject, text: JString) {
+    let text = JniUtils::to_string(&mut env, text);
+    send_event(RuffleEvent::HandleInput(InputEvent::Text(TextEvent::Composing(text))));
+}
+
+fn em_delete_surrounding_text(_env: JNIEnv, _thiz: JObject, before: jint, after: jint) {
+    send_event(RuffleEvent::HandleInput(InputEvent::Text(TextEvent::DeleteSurrounding(
+        before.max(0) as usize,
+        after.max(0) as usize,
+    ))));
+}
+
+/// Whether the host answers `ExternalInterface.call` for `name` in `onExternalCall`.
+fn em_set_external_callback(mut env: JNIEnv, _thiz: JObject, name: JString, registered: jboolean) {
+    set_host_callback(JniUtils::to_string(&mut env, name), registered == JNI_TRUE);
+}
+
+/// Calls a function the movie registered with `ExternalInterface.addCallback`. The result
+/// arrives later in `onExternalCallResult` with the same `id`, null if the call failed.
+/// Arguments `from_java` can't read throw back to the host instead.
+fn em_call_external(mut env: JNIEnv, _thiz: JObject, name: JString, args: JObjectArray, id: jint) {
+    let name = JniUtils::to_string(&mut env, name);
+    let result = env.with_local_frame(8, |env| {
+        let len = if args.is_null() { 0 } else { env.get_array_length(&args)? };
+        let mut values = Vec::with_capacity(len as usize);
+        for i in 0..len {
+            let arg = env.get_object_array_element(&args, i)?;
+            values.push(from_java(env, &arg)?);
+            env.delete_local_ref(arg)?;
+        }
+        Ok(values)
+    });
+    match result {
+        Ok(args) => send_event(RuffleEvent::CallExternal(name, args, id)),
+        Err(e) => error!("Bad arguments for movie callback {name}! {e}"),
+    }
+}
+
+/// Answers `onFileOpenRequested` with the document's name and contents, or `onFileSaveRequested`
+/// with its name and a detached descriptor open for writing. A null name cancels.
+fn em_file_dialog_result(mut env: JNIEnv, _thiz: JObject, id: jint, name: JString, contents: JByteArray, fd: jint) {
+    let answer = if name.is_null() {
+        DialogAnswer::Cancelled
+    } else {
+        let name = JniUtils::to_string(&mut env, name);
+        if fd >= 0 {
+            // Detached by the host, so closing it is up to us.
+            DialogAnswer::Created { name, file: unsafe { File::from_raw_fd(fd) } }
+        } else if contents.is_null() {
+            DialogAnswer::Cancelled
+        } else {
+            match env.convert_byte_array(&contents) {
+                Ok(contents) => DialogAnswer::Opened { name, contents },
+                Err(e) => {
+                    error!("Failed to read picked file {name}! {e}");
+                    DialogAnswer::Cancelled
+                }
+            }
+        }
+    };
+    filedialog::answer(id, answer);
+}
+
+/// The host reports every change of the primary clip, null when it holds no text.
+fn em_set_clipboard_text(mut env: JNIEnv, _thiz: JObject, text: JString) {
+    let text = if text.is_null() {
+        String::new()
+    } else {
+        JniUtils::to_string(&mut env, text)
+    };
+    mirror_clipboard(text);
+}
+
+/// Clamped to 0.25x to 8x, also leaves frame stepping.
+fn em_set_speed(_env: JNIEnv, _thiz: JObject, speed: jfloat) {
+    send_event(RuffleEvent::SetSpeed(speed as f64));
+}
+
+/// Pauses the movie if needed and runs a single frame.
+fn em_step_frame(_env: JNIEnv, _thiz: JObject) {
+    send_event(RuffleEvent::StepFrame);
+}
+
+/// A negative device id targets the default profile, a null profile removes it.
+fn em_set_remap_profile(mut env: JNIEnv, _thiz: JObject, profile: JString, device_id: jint) -> jboolean {
+    let device_id = (device_id >= 0).then_some(device_id);
+    if profile.is_null() {
+        send_event(RuffleEvent::SetRemapProfile(device_id, None));
+        return JNI_TRUE;
+    }
+    match RemapProfile::load(&JniUtils::to_string(&mut env, profile)) {
+        Ok(profile) => {
+            send_event(RuffleEvent::SetRemapProfile(device_id, Some(profile)));
+            JNI_TRUE
+        }
+        Err(e) => {
+            error!("Failed to load remap profile! {e}");
+            JNI_FALSE
+        }
+    }
+}
+
+fn em_dispatch_touch_event(mut env: JNIEnv, _thiz: JObject, event: JObject) -> jboolean {
+    let action = env.call_method(&event, "getActionMasked", "()I", &[])
+        .expect("Failed to call MotionEvent::getActionMasked() method!")
+        .i()
+        .expect("Failed to call MotionEvent::getActionMasked() method!");
+    let action_index = env.call_method(&event, "getActionIndex", "()I", &[])
+        .expect("Failed to call MotionEvent::getActionIndex() method!")
+        .i()
+        .expect("Failed to call MotionEvent::getActionIndex() method!");
+    let pointer_count = env.call_method(&event, "getPointerCount", "()I", &[])
+        .expect("Failed to call MotionEvent::getPointerCount() method!")
+        .i()
+        .expect("Failed to call MotionEvent::getPointerCount() method!");
+    let mut pointers = Vec::with_capacity(pointer_count as usize);
+    for index in 0..pointer_count {
+        let args = [JValue::from(index)];
+        let id = env.call_method(&event, "getPointerId", "(I)I", &args)
+            .and_then(|it| it.i())
+            .expect("Failed to call MotionEvent::getPointerId() method!");
+        let x = env.call_method(&event, "getX", "(I)F", &args)
+            .and_then(|it| it.f())
+            .expect("Failed to call MotionEvent::getX() method!");
+        let y = env.call_method(&event, "getY", "(I)F", &args)
+            .and_then(|it| it.f())
+            .expect("Failed to call MotionEvent::getY() method!");
+        pointers.push(TouchPointer { id, x: x as f64, y: y as f64 });
+    }
+    let pointer_id = pointers.get(action_index as usize).map_or(0, |it| it.id);
+    send_event(RuffleEvent::HandleInput(InputEvent::Touch(TouchEvent::new(TouchAction::from(action), pointer_id, pointers))));
     JNI_TRUE
 }
 
-fn em_dispatch_touch_event(_env: JNIEnv, _thiz: JObject, x: jfloat, y: jfloat, pressed: jboolean) -> jboolean {
-    send_event(RuffleEvent::HandleTouchEvent(TouchEvent::new(x as f64, y as f64, KeyAction::from(pressed == JNI_TRUE))));
+fn em_dispatch_pointer_event(mut env: JNIEnv, _thiz: JObject, event: JObject) -> jboolean {
+    let action = env.call_method(&event, "getActionMasked", "()I", &[])
+        .and_then(|it| it.i())
+        .expect("Failed to call MotionEvent::getActionMasked() method!");
+    let x = env.call_method(&event, "getX", "()F", &[])
+        .and_then(|it| it.f())
+        .expect("Failed to call MotionEvent::getX() method!");
+    let y = env.call_method(&event, "getY", "()F", &[])
+        .and_then(|it| it.f())
+        .expect("Failed to call MotionEvent::getY() method!");
+    let button_state = env.call_method(&event, "getButtonState", "()I", &[])
+        .and_then(|it| it.i())
+        .expect("Failed to call MotionEvent::getButtonState() method!");
+    let scroll = env.call_method(&event, "getAxisValue", "(I)F", &[JValue::from(AXIS_VSCROLL)])
+        .and_then(|it| it.f())
+        .expect("Failed to call MotionEvent::getAxisValue() method!");
+    send_event(RuffleEvent::HandleInput(InputEvent::Pointer(PointerEvent::new(
+        PointerAction::from(action),
+        x as f64,
+        y as f64,
+        button_state,
+        scroll as f64,
+    ))));
     JNI_TRUE
 }
 
 #[unsafe(no_mangle)]
 pub extern "C" fn JNI_OnLoad(vm: JavaVM, _reserved: *const c_void) -> jint {
+    #[cfg(target_os = "android")]
     android_logger::init_once(
         android_logger::Config::default()
-            .with_max_level(LevelFilter::Info)
+            .with_max_level(log::LevelFilter::Info)
             .with_tag("libruffle"),
     );
     let mut env = vm.get_env().unwrap();
@@ -334,8 +1124,88 @@ pub extern "C" fn JNI_OnLoad(vm: JavaVM, _reserved: *const c_void) -> jint {
         },
         NativeMethod {
             name: "nativeDispatchTouchEvent".into(),
-            sig: "(FFZ)Z".into(),
+            sig: "(Landroid/view/MotionEvent;)Z".into(),
             fn_ptr: em_dispatch_touch_event as *mut _,
+        },
+        NativeMethod {
+            name: "nativeDispatchPointerEvent".into(),
+            sig: "(Landroid/view/MotionEvent;)Z".into(),
+            fn_ptr: em_dispatch_pointer_event as *mut _,
+        },
+        NativeMethod {
+            name: "nativeDispatchJoystickEvent".into(),
+            sig: "(Landroid/view/MotionEvent;)Z".into(),
+            fn_ptr: em_dispatch_joystick_event as *mut _,
+        },
+        NativeMethod {
+            name: "nativeSetRemapProfile".into(),
+            sig: "(Ljava/lang/String;I)Z".into(),
+            fn_ptr: em_set_remap_profile as *mut _,
+        },
+        NativeMethod {
+            name: "nativeGetAudioLatencyMs".into(),
+            sig: "()F".into(),
+            fn_ptr: em_get_audio_latency_ms as *mut _,
+        },
+        NativeMethod {
+            name: "nativeSetAudioBufferBursts".into(),
+            sig: "(I)V".into(),
+            fn_ptr: em_set_audio_buffer_bursts as *mut _,
+        },
+        NativeMethod {
+            name: "nativeAudioDeviceChanged".into(),
+            sig: "()V".into(),
+            fn_ptr: em_audio_device_changed as *mut _,
+        },
+        NativeMethod {
+            name: "nativeGetStats".into(),
+            sig: "()Landroid/os/Bundle;".into(),
+            fn_ptr: em_get_stats as *mut _,
+        },
+        NativeMethod {
+            name: "nativeCommitText".into(),
+            sig: "(Ljava/lang/String;)V".into(),
+            fn_ptr: em_commit_text as *mut _,
+        },
+        NativeMethod {
+            name: "nativeSetComposingText".into(),
+            sig: "(Ljava/lang/String;)V".into(),
+            fn_ptr: em_set_composing_text as *mut _,
+        },
+        NativeMethod {
+            name: "nativeDeleteSurroundingText".into(),
+            sig: "(II)V".into(),
+            fn_ptr: em_delete_surrounding_text as *mut _,
+        },
+        NativeMethod {
+            name: "nativeSetSpeed".into(),
+            sig: "(F)V".into(),
+            fn_ptr: em_set_speed as *mut _,
+        },
+        NativeMethod {
+            name: "nativeStepFrame".into(),
+            sig: "()V".into(),
+            fn_ptr: em_step_frame as *mut _,
+        },
+        NativeMethod {
+            name: "nativeSetClipboardText".into(),
+            sig: "(Ljava/lang/String;)V".into(),
+            fn_ptr: em_set_clipboard_text as *mut _,
+        },
+        NativeMethod {
+            name: "nativeFileDialogResult".into(),
+            sig: "(ILjava/lang/String;[BI)V".into(),
+            fn_ptr: em_file_dialog_result as *mut _,
+        },
+        NativeMethod {
+            name: "nativeSetExternalCallback".into(),
+            sig: "(Ljava/lang/String;Z)V".into(),
+            fn_ptr: em_set_external_callback as *mut _,
+        },
+        NativeMethod {
+            name: "nativeCallExternal".into(),
+            sig: "(Ljava/lang/String;[Ljava/lang/Object;I)V".into(),
+            fn_ptr: em_call_external as *mut _,
         }
     ];
     assert!(
@@ -357,14 +1227,3 @@ pub extern "C" fn JNI_OnUnload(
         .unwrap();
     let _ = env.unregister_native_methods("org/wkuwku/plug/ruffle/Ruffle");
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
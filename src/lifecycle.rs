@@ -0,0 +1,102 @@
+use std::fmt::Display;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum LifecycleState {
+    /// Surface attached: tick, render and play audio.
+    Foreground,
+    /// Surface gone but audio kept alive: tick without rendering.
+    Background,
+    /// Surface gone: no ticking, audio stream paused.
+    Suspended,
+}
+
+impl Display for LifecycleState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                LifecycleState::Foreground => "Foreground",
+                LifecycleState::Background => "Background",
+                LifecycleState::Suspended => "Suspended",
+            }
+        )
+    }
+}
+
+pub struct Lifecycle {
+    state: LifecycleState,
+    pub keep_audio_in_background: bool,
+    /// Whether the movie was playing when it got suspended, restored on resume.
+    was_playing: bool,
+}
+
+impl Lifecycle {
+    pub fn new(keep_audio_in_background: bool) -> Self {
+        Self {
+            state: LifecycleState::Suspended,
+            keep_audio_in_background,
+            was_playing: true,
+        }
+    }
+
+    pub fn should_tick(&self) -> bool {
+        self.state != LifecycleState::Suspended
+    }
+
+    pub fn should_render(&self) -> bool {
+        self.state == LifecycleState::Foreground
+    }
+
+    /// Returns the previous state.
+    pub fn on_surface_attached(&mut self) -> LifecycleState {
+        std::mem::replace(&mut self.state, LifecycleState::Foreground)
+    }
+
+    /// Returns the new state. `is_playing` is remembered if the movie gets suspended.
+    pub fn on_surface_detached(&mut self, is_playing: bool) -> LifecycleState {
+        if self.state == LifecycleState::Foreground {
+            self.state = if self.keep_audio_in_background {
+                LifecycleState::Background
+            } else {
+                self.was_playing = is_playing;
+                LifecycleState::Suspended
+            };
+        }
+        self.state
+    }
+
+    pub fn was_playing(&self) -> bool {
+        self.was_playing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detach_suspends_and_remembers_playback() {
+        let mut lifecycle = Lifecycle::new(false);
+        assert_eq!(lifecycle.on_surface_attached(), LifecycleState::Suspended);
+        assert!(lifecycle.should_render());
+
+        assert_eq!(lifecycle.on_surface_detached(false), LifecycleState::Suspended);
+        assert!(!lifecycle.should_tick());
+        assert!(!lifecycle.was_playing());
+
+        // A second detach must not overwrite what was saved.
+        assert_eq!(lifecycle.on_surface_detached(true), LifecycleState::Suspended);
+        assert!(!lifecycle.was_playing());
+    }
+
+    #[test]
+    fn detach_keeps_ticking_with_background_audio() {
+        let mut lifecycle = Lifecycle::new(true);
+        lifecycle.on_surface_attached();
+        assert_eq!(lifecycle.on_surface_detached(true), LifecycleState::Background);
+        assert!(lifecycle.should_tick());
+        assert!(!lifecycle.should_render());
+        assert_eq!(lifecycle.on_surface_attached(), LifecycleState::Background);
+    }
+}
@@ -1,52 +1,251 @@
-use ndk::audio::{AudioDirection, AudioFormat, AudioStream, AudioStreamBuilder, AudioStreamState};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Seek, SeekFrom, Write},
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use log::{error, info, warn};
+use ndk::audio::{
+    AudioDirection, AudioError, AudioFormat, AudioStream, AudioStreamBuilder, Clockid,
+};
 use ruffle_core::backend::audio::{
-    swf, AudioBackend, AudioMixer, DecodeError, RegisterError, SoundHandle, SoundInstanceHandle,
-    SoundStreamInfo, SoundTransform,
+    swf, AudioBackend, AudioMixer, AudioMixerProxy, DecodeError, RegisterError, SoundHandle,
+    SoundInstanceHandle, SoundStreamInfo, SoundTransform,
 };
 
 use ruffle_core::impl_audio_mixer_backend;
 
-pub struct AAudioAudioBackend {
-    pub stream: Option<AudioStream>,
+pub const DEFAULT_BUFFER_BURSTS: i32 = 2;
+
+const RECOVERY_MAX_ATTEMPTS: u32 = 8;
+const RECOVERY_INITIAL_BACKOFF: Duration = Duration::from_millis(50);
+const RECOVERY_MAX_BACKOFF: Duration = Duration::from_secs(2);
+
+const LATENCY_SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+const LATENCY_SMOOTHING: f64 = 0.2;
+
+const FALLBACK_SAMPLE_RATE: u32 = 44100;
+const FALLBACK_CHANNEL_COUNT: u8 = 2;
+
+/// Called from the AAudio callback thread. Must not touch the stream itself,
+/// only hand the error over to the worker thread.
+pub type StreamErrorListener = Arc<dyn Fn(AudioError) + Send + Sync>;
+
+type Error = Box<dyn std::error::Error>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RecoveryState {
+    Healthy,
+    Pending { attempts: u32, retry_at: Instant },
+    Failed,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RecoveryOutcome {
+    /// The stream was reopened, possibly on a different output device.
+    Recovered { device_id: i32 },
+    /// Every retry failed and the stream is closed, so no error callback fires again.
+    /// Audio stays silent until `retry`, on resume or when the output devices change.
+    GaveUp,
+}
+
+/// Destination of the mixed audio. The sink decides the output format,
+/// the backend creates its mixer to match and hands the sink a proxy to pull from.
+pub trait AudioSink {
+    fn channel_count(&self) -> u8;
+
+    fn sample_rate(&self) -> u32;
+
+    fn open(&mut self, proxy: AudioMixerProxy) -> Result<(), Error>;
+
+    fn start(&mut self) -> Result<(), Error>;
+
+    fn pause(&mut self) -> Result<(), Error>;
+
+    /// Called on every iteration of the worker loop.
+    fn poll(&mut self, _now: Instant) -> Option<RecoveryOutcome> {
+        None
+    }
+
+    /// Called after every player tick with the movie time it advanced.
+    fn on_tick(&mut self, _frame_time: Duration) {}
+
+    fn on_stream_error(&mut self, _err: AudioError) {}
+
+    /// Starts recovery over after it gave up.
+    fn retry(&mut self) {}
+
+    /// Returns the buffer size actually granted in frames, 0 without a device buffer.
+    fn set_buffer_bursts(&mut self, _buffer_bursts: i32) -> Result<i32, Error> {
+        Ok(0)
+    }
+
+    /// Time between a frame being mixed and it reaching the speaker, if known.
+    fn latency(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Times the device ran out of audio to play since the sink was created.
+    fn underruns(&self) -> u64 {
+        0
+    }
+}
+
+pub struct MixerAudioBackend {
     pub mixer: AudioMixer,
+    pub sink: Box<dyn AudioSink>,
     pub paused: bool,
 }
 
-type Error = Box<dyn std::error::Error>;
+impl MixerAudioBackend {
+    pub fn new(mut sink: Box<dyn AudioSink>) -> Result<Self, Error> {
+        let mixer = AudioMixer::new(sink.channel_count(), sink.sample_rate());
+        sink.open(mixer.proxy())?;
+        Ok(Self {
+            mixer,
+            sink,
+            paused: true,
+        })
+    }
 
-impl AAudioAudioBackend {
-    pub fn new() -> Result<Self, Error> {
-        let mixer = AudioMixer::new(2, 44100);
+    pub fn poll(&mut self, now: Instant) -> Option<RecoveryOutcome> {
+        self.sink.poll(now)
+    }
+
+    pub fn on_tick(&mut self, frame_time: Duration) {
+        self.sink.on_tick(frame_time);
+    }
+
+    pub fn on_stream_error(&mut self, err: AudioError) {
+        self.sink.on_stream_error(err);
+    }
+
+    pub fn retry(&mut self) {
+        self.sink.retry();
+    }
+
+    pub fn set_buffer_bursts(&mut self, buffer_bursts: i32) {
+        match self.sink.set_buffer_bursts(buffer_bursts) {
+            Ok(frames) => info!("Audio buffer set to {buffer_bursts} bursts, {frames} frames granted."),
+            Err(e) => error!("Failed to resize audio buffer! {e}"),
+        }
+    }
+
+    pub fn latency(&self) -> Option<Duration> {
+        self.sink.latency()
+    }
+
+    pub fn underruns(&self) -> u64 {
+        self.sink.underruns()
+    }
+}
+
+impl AudioBackend for MixerAudioBackend {
+    impl_audio_mixer_backend!(mixer);
 
-        let mut result = Self {
+    fn play(&mut self) {
+        self.paused = false;
+        if let Err(e) = self.sink.start() {
+            error!("Error trying to resume audio output. {e}");
+        }
+    }
+
+    fn pause(&mut self) {
+        self.paused = true;
+        if let Err(e) = self.sink.pause() {
+            error!("Error trying to pause audio output. {e}");
+        }
+    }
+}
+
+/// Stream parameters granted by the device when the sink was created.
+/// Reopened streams keep requesting the same values, since the mixer
+/// cannot change its output format once it exists.
+#[derive(Clone, Copy, Debug)]
+pub struct StreamConfig {
+    pub sample_rate: i32,
+    pub channel_count: i32,
+    pub frames_per_burst: i32,
+    pub buffer_bursts: i32,
+}
+
+pub struct AAudioSink {
+    pub stream: Option<AudioStream>,
+    pub paused: bool,
+    pub config: StreamConfig,
+    pub recovery: RecoveryState,
+    latency: Option<Duration>,
+    latency_sampled_at: Option<Instant>,
+    /// Underruns of the streams closed so far, AAudio counts per stream.
+    closed_underruns: u64,
+    proxy: Option<Arc<AudioMixerProxy>>,
+    error_listener: StreamErrorListener,
+}
+
+impl AAudioSink {
+    pub fn new(buffer_bursts: i32, error_listener: StreamErrorListener) -> Result<Self, Error> {
+        Ok(Self {
             stream: None,
-            mixer,
             paused: true,
-        };
+            config: Self::negotiate_config(buffer_bursts)?,
+            recovery: RecoveryState::Healthy,
+            latency: None,
+            latency_sampled_at: None,
+            closed_underruns: 0,
+            proxy: None,
+            error_listener,
+        })
+    }
 
-        result.open_stream()?;
+    /// Opens a throwaway stream without a rate or channel count so AAudio
+    /// picks the device's native format, and reads back what was granted.
+    fn negotiate_config(buffer_bursts: i32) -> Result<StreamConfig, Error> {
+        let probe = AudioStreamBuilder::new()?
+            .direction(AudioDirection::Output)
+            .format(AudioFormat::PCM_Float)
+            .performance_mode(ndk::audio::AudioPerformanceMode::LowLatency)
+            .open_stream()?;
 
-        Ok(result)
+        Ok(StreamConfig {
+            sample_rate: probe.sample_rate(),
+            // The mixer only produces mono or stereo output.
+            channel_count: probe.channel_count().clamp(1, 2),
+            frames_per_burst: probe.frames_per_burst(),
+            buffer_bursts: buffer_bursts.max(1),
+        })
     }
 
-    pub fn open_stream(&mut self) -> Result<(), Error> {
-        let proxy = self.mixer.proxy();
+    fn open_stream(&mut self) -> Result<(), Error> {
+        let proxy = self.proxy.clone().ok_or("Audio sink opened without a mixer")?;
+        let channel_count = self.config.channel_count;
+        let error_listener = self.error_listener.clone();
 
         let stream = AudioStreamBuilder::new()?
             .direction(AudioDirection::Output)
             .format(AudioFormat::PCM_Float)
-            .channel_count(2)
-            .sample_rate(44100)
+            .channel_count(channel_count)
+            .sample_rate(self.config.sample_rate)
             .performance_mode(ndk::audio::AudioPerformanceMode::LowLatency)
             .data_callback(Box::new(move |_stream, data, len| {
                 let sl = unsafe {
-                    std::slice::from_raw_parts_mut::<f32>(data as *mut f32, len as usize * 2)
+                    std::slice::from_raw_parts_mut::<f32>(
+                        data as *mut f32,
+                        len as usize * channel_count as usize,
+                    )
                 };
                 proxy.mix(sl);
                 ndk::audio::AudioCallbackResult::Continue
             }))
+            .error_callback(Box::new(move |_stream, err| error_listener(err)))
             .open_stream()?;
 
+        if stream.frames_per_burst() > 0 {
+            self.config.frames_per_burst = stream.frames_per_burst();
+        }
+        stream.set_buffer_size_in_frames(self.config.frames_per_burst * self.config.buffer_bursts)?;
+
         if !self.paused {
             stream.request_start()?;
         }
@@ -55,33 +254,514 @@ impl AAudioAudioBackend {
         Ok(())
     }
 
-    pub fn keep_stream_valid(&mut self) {
-        let stream_state = self.stream.as_ref().unwrap().state();
-        if stream_state == AudioStreamState::Disconnected {
-            // I'm sure it's fine...
-            let _ = self.open_stream();
+    /// Estimates when the next written frame will be presented, from the
+    /// position and time AAudio last reported for the hardware.
+    fn measure_latency(&self) -> Option<Duration> {
+        let stream = self.stream.as_ref()?;
+        let timestamp = stream.timestamp(Clockid::Monotonic).ok()?;
+        let frames_ahead = stream.frames_written() - timestamp.frame_position;
+        let presented_at = timestamp.time_nanoseconds
+            + frames_ahead * 1_000_000_000 / self.config.sample_rate as i64;
+        let latency = presented_at - monotonic_nanos();
+        (latency >= 0).then(|| Duration::from_nanos(latency as u64))
+    }
+
+    fn sample_latency(&mut self, now: Instant) {
+        if self.paused
+            || self
+                .latency_sampled_at
+                .is_some_and(|at| now.duration_since(at) < LATENCY_SAMPLE_INTERVAL)
+        {
+            return;
+        }
+        self.latency_sampled_at = Some(now);
+        if let Some(measured) = self.measure_latency() {
+            self.latency = Some(match self.latency {
+                Some(latency) => latency.mul_f64(1.0 - LATENCY_SMOOTHING)
+                    + measured.mul_f64(LATENCY_SMOOTHING),
+                None => measured,
+            });
         }
     }
+
 }
 
-impl AudioBackend for AAudioAudioBackend {
-    impl_audio_mixer_backend!(mixer);
+impl AudioSink for AAudioSink {
+    fn channel_count(&self) -> u8 {
+        self.config.channel_count as u8
+    }
 
-    fn play(&mut self) {
-        self.stream
-            .as_mut()
-            .expect("Error trying to resume audio stream.")
-            .request_start()
-            .expect("Error trying to resume audio stream.");
+    fn sample_rate(&self) -> u32 {
+        self.config.sample_rate as u32
+    }
+
+    fn open(&mut self, proxy: AudioMixerProxy) -> Result<(), Error> {
+        self.proxy = Some(Arc::new(proxy));
+        self.open_stream()
+    }
+
+    // The intent is recorded first so a stream reopened by recovery
+    // starts in whatever state Ruffle asked for last.
+    fn start(&mut self) -> Result<(), Error> {
         self.paused = false;
+        self.retry();
+        if let Some(stream) = &self.stream {
+            if let Err(e) = stream.request_start() {
+                self.on_stream_error(e);
+                return Err(e.into());
+            }
+        }
+        Ok(())
+    }
+
+    fn pause(&mut self) -> Result<(), Error> {
+        self.paused = true;
+        if let Some(stream) = &self.stream {
+            if let Err(e) = stream.request_pause() {
+                self.on_stream_error(e);
+                return Err(e.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Reopens a broken stream. This never runs inside the AAudio callback,
+    /// which is not allowed to close or open streams.
+    fn poll(&mut self, now: Instant) -> Option<RecoveryOutcome> {
+        let RecoveryState::Pending { attempts, retry_at } = self.recovery else {
+            self.sample_latency(now);
+            return None;
+        };
+        if now < retry_at {
+            return None;
+        }
+        if let Some(stream) = self.stream.take() {
+            self.closed_underruns += stream.x_run_count().max(0) as u64;
+            let _ = stream.request_stop();
+        }
+        match self.open_stream() {
+            Ok(()) => {
+                self.recovery = RecoveryState::Healthy;
+                // A new route usually means a different latency, e.g. Bluetooth.
+                self.latency = None;
+                let device_id = self.stream.as_ref().map_or(0, |stream| stream.device_id());
+                info!("Audio stream recovered on device {device_id}.");
+                Some(RecoveryOutcome::Recovered { device_id })
+            }
+            Err(e) if attempts + 1 >= RECOVERY_MAX_ATTEMPTS => {
+                error!("Giving up on audio stream recovery! {e}");
+                self.recovery = RecoveryState::Failed;
+                Some(RecoveryOutcome::GaveUp)
+            }
+            Err(e) => {
+                let backoff = RECOVERY_INITIAL_BACKOFF
+                    .saturating_mul(1 << attempts)
+                    .min(RECOVERY_MAX_BACKOFF);
+                warn!("Failed to reopen audio stream, retrying in {backoff:?}. {e}");
+                self.recovery = RecoveryState::Pending {
+                    attempts: attempts + 1,
+                    retry_at: now + backoff,
+                };
+                None
+            }
+        }
+    }
+
+    fn on_stream_error(&mut self, err: AudioError) {
+        warn!("Audio stream error: {err}");
+        if !matches!(self.recovery, RecoveryState::Pending { .. }) {
+            self.recovery = RecoveryState::Pending {
+                attempts: 0,
+                retry_at: Instant::now(),
+            };
+        }
+    }
+
+    /// Resizes the stream buffer to a multiple of the device burst size, reopened
+    /// streams keep the new size.
+    fn set_buffer_bursts(&mut self, buffer_bursts: i32) -> Result<i32, Error> {
+        self.config.buffer_bursts = buffer_bursts.max(1);
+        match &self.stream {
+            Some(stream) => Ok(stream
+                .set_buffer_size_in_frames(self.config.frames_per_burst * self.config.buffer_bursts)?),
+            None => Ok(0),
+        }
+    }
+
+    fn retry(&mut self) {
+        if self.recovery == RecoveryState::Failed {
+            info!("Retrying audio stream recovery.");
+            self.recovery = RecoveryState::Pending {
+                attempts: 0,
+                retry_at: Instant::now(),
+            };
+        }
+    }
+
+    fn latency(&self) -> Option<Duration> {
+        self.latency
+    }
+
+    fn underruns(&self) -> u64 {
+        let current = self.stream.as_ref().map_or(0, |stream| stream.x_run_count().max(0) as u64);
+        self.closed_underruns + current
+    }
+}
+
+fn monotonic_nanos() -> i64 {
+    let mut ts = ndk_sys::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    unsafe {
+        ndk_sys::clock_gettime(ndk_sys::CLOCK_MONOTONIC as ndk_sys::clockid_t, &mut ts);
+    }
+    ts.tv_sec as i64 * 1_000_000_000 + ts.tv_nsec as i64
+}
+
+/// Holds the timeline back by the output latency plus a user offset, so stream
+/// sounds that keep mixing on their own are heard together with their frames.
+/// The offset is eased in by at most one tick's worth of time per tick.
+pub struct AvSync {
+    pub enabled: bool,
+    pub offset_ms: f64,
+    held_ms: f64,
+}
+
+impl AvSync {
+    pub fn new(enabled: bool, offset_ms: f64) -> Self {
+        Self {
+            enabled,
+            offset_ms,
+            held_ms: 0.0,
+        }
+    }
+
+    /// Returns how far the timeline may advance for a tick of `dt` milliseconds.
+    pub fn adjust(&mut self, dt: f64, latency: Option<Duration>) -> f64 {
+        let target = if self.enabled {
+            latency.map_or(0.0, |latency| latency.as_secs_f64() * 1000.0) + self.offset_ms
+        } else {
+            0.0
+        };
+        let delta = (target - self.held_ms).clamp(-dt, dt);
+        self.held_ms += delta;
+        dt - delta
+    }
+}
+
+/// Pulls from the mixer at the pace of the worker loop or the movie rather than
+/// a device, so sinks without a hardware clock still let sounds play out and finish.
+struct ClockedPull {
+    proxy: Option<AudioMixerProxy>,
+    channel_count: u8,
+    sample_rate: u32,
+    last_pull: Option<Instant>,
+    pending_frames: f64,
+    buffer: Vec<f32>,
+}
+
+impl ClockedPull {
+    fn new(channel_count: u8, sample_rate: u32) -> Self {
+        Self {
+            proxy: None,
+            channel_count,
+            sample_rate,
+            last_pull: None,
+            pending_frames: 0.0,
+            buffer: Vec::new(),
+        }
     }
 
     fn pause(&mut self) {
-        self.stream
-            .as_mut()
-            .expect("Error trying to pause audio stream.")
-            .request_pause()
-            .expect("Error trying to pause audio stream.");
+        self.last_pull = None;
+    }
+
+    /// Pulls what played since the last call on the wall clock.
+    fn pull(&mut self, now: Instant) -> &[f32] {
+        match self.last_pull.replace(now) {
+            Some(last_pull) => self.pull_for(now.duration_since(last_pull)),
+            None => &[],
+        }
+    }
+
+    /// Pulls `elapsed` worth of frames, the same count every time for the same durations.
+    fn pull_for(&mut self, elapsed: Duration) -> &[f32] {
+        let Some(proxy) = &self.proxy else {
+            return &[];
+        };
+        self.pending_frames += elapsed.as_secs_f64() * self.sample_rate as f64;
+        let frames = self.pending_frames.floor();
+        self.pending_frames -= frames;
+        self.buffer.clear();
+        self.buffer.resize(frames as usize * self.channel_count as usize, 0.0);
+        proxy.mix(&mut self.buffer);
+        &self.buffer
+    }
+}
+
+/// Discards everything, for headless runs.
+pub struct NullSink {
+    pull: ClockedPull,
+    paused: bool,
+}
+
+impl NullSink {
+    pub fn new() -> Self {
+        Self {
+            pull: ClockedPull::new(FALLBACK_CHANNEL_COUNT, FALLBACK_SAMPLE_RATE),
+            paused: true,
+        }
+    }
+}
+
+impl AudioSink for NullSink {
+    fn channel_count(&self) -> u8 {
+        self.pull.channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.pull.sample_rate
+    }
+
+    fn open(&mut self, proxy: AudioMixerProxy) -> Result<(), Error> {
+        self.pull.proxy = Some(proxy);
+        Ok(())
+    }
+
+    fn start(&mut self) -> Result<(), Error> {
+        self.paused = false;
+        Ok(())
+    }
+
+    fn pause(&mut self) -> Result<(), Error> {
+        self.paused = true;
+        self.pull.pause();
+        Ok(())
+    }
+
+    fn poll(&mut self, now: Instant) -> Option<RecoveryOutcome> {
+        if !self.paused {
+            self.pull.pull(now);
+        }
+        None
+    }
+}
+
+/// Records everything the movie plays into a 32-bit float WAV file. Paced by the
+/// movie's ticks, so the same run always records the same samples.
+pub struct WavFileSink {
+    pull: ClockedPull,
+    writer: WavWriter<BufWriter<File>>,
+    paused: bool,
+}
+
+impl WavFileSink {
+    pub fn new(path: &Path) -> Result<Self, Error> {
+        let writer = WavWriter::new(
+            BufWriter::new(File::create(path)?),
+            FALLBACK_CHANNEL_COUNT as u16,
+            FALLBACK_SAMPLE_RATE,
+        )?;
+        Ok(Self {
+            pull: ClockedPull::new(FALLBACK_CHANNEL_COUNT, FALLBACK_SAMPLE_RATE),
+            writer,
+            paused: true,
+        })
+    }
+}
+
+impl AudioSink for WavFileSink {
+    fn channel_count(&self) -> u8 {
+        self.pull.channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.pull.sample_rate
+    }
+
+    fn open(&mut self, proxy: AudioMixerProxy) -> Result<(), Error> {
+        self.pull.proxy = Some(proxy);
+        Ok(())
+    }
+
+    fn start(&mut self) -> Result<(), Error> {
+        self.paused = false;
+        Ok(())
+    }
+
+    fn pause(&mut self) -> Result<(), Error> {
         self.paused = true;
+        Ok(self.writer.flush()?)
+    }
+
+    fn on_tick(&mut self, frame_time: Duration) {
+        if !self.paused {
+            let samples = self.pull.pull_for(frame_time);
+            if let Err(e) = self.writer.write_samples(samples) {
+                error!("Failed to write audio samples! {e}");
+            }
+        }
+    }
+}
+
+/// Minimal WAV encoder. The RIFF and data sizes are patched in on every flush
+/// so the file stays playable even if the process is killed.
+pub struct WavWriter<W: Write + Seek> {
+    inner: W,
+    data_len: u32,
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    const HEADER_LEN: u32 = 44;
+    const FORMAT_IEEE_FLOAT: u16 = 3;
+
+    pub fn new(mut inner: W, channel_count: u16, sample_rate: u32) -> io::Result<Self> {
+        let block_align = channel_count * 4;
+        inner.write_all(b"RIFF")?;
+        inner.write_all(&(Self::HEADER_LEN - 8).to_le_bytes())?;
+        inner.write_all(b"WAVEfmt ")?;
+        inner.write_all(&16u32.to_le_bytes())?;
+        inner.write_all(&Self::FORMAT_IEEE_FLOAT.to_le_bytes())?;
+        inner.write_all(&channel_count.to_le_bytes())?;
+        inner.write_all(&sample_rate.to_le_bytes())?;
+        inner.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+        inner.write_all(&block_align.to_le_bytes())?;
+        inner.write_all(&32u16.to_le_bytes())?;
+        inner.write_all(b"data")?;
+        inner.write_all(&0u32.to_le_bytes())?;
+        Ok(Self { inner, data_len: 0 })
+    }
+
+    pub fn write_samples(&mut self, samples: &[f32]) -> io::Result<()> {
+        for sample in samples {
+            self.inner.write_all(&sample.to_le_bytes())?;
+        }
+        self.data_len += samples.len() as u32 * 4;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        let end = self.inner.stream_position()?;
+        self.inner.seek(SeekFrom::Start(4))?;
+        self.inner.write_all(&(Self::HEADER_LEN - 8 + self.data_len).to_le_bytes())?;
+        self.inner.seek(SeekFrom::Start(40))?;
+        self.inner.write_all(&self.data_len.to_le_bytes())?;
+        self.inner.seek(SeekFrom::Start(end))?;
+        self.inner.flush()
+    }
+}
+
+impl<W: Write + Seek> Drop for WavWriter<W> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn av_sync_eases_in_latency_and_offset() {
+        let mut sync = AvSync::new(true, 10.0);
+        let latency = Some(Duration::from_millis(30));
+        assert_eq!(sync.adjust(16.0, latency), 0.0);
+        assert_eq!(sync.adjust(16.0, latency), 0.0);
+        assert_eq!(sync.adjust(16.0, latency), 8.0);
+        assert_eq!(sync.adjust(16.0, latency), 16.0);
+
+        sync.enabled = false;
+        assert_eq!(sync.adjust(16.0, latency), 32.0);
+        assert_eq!(sync.adjust(16.0, latency), 32.0);
+        assert_eq!(sync.adjust(16.0, latency), 24.0);
+        assert_eq!(sync.adjust(16.0, latency), 16.0);
+    }
+
+    #[test]
+    fn ticked_pulls_carry_partial_frames() {
+        let mixer = AudioMixer::new(2, 44100);
+        let mut pull = ClockedPull::new(2, 44100);
+        pull.proxy = Some(mixer.proxy());
+        let step = Duration::from_millis(25);
+        assert_eq!(pull.pull_for(step).len(), 1102 * 2);
+        assert_eq!(pull.pull_for(step).len(), 1103 * 2);
+        assert_eq!(pull.pull_for(step).len(), 1102 * 2);
+    }
+
+    #[test]
+    fn wav_header_describes_float_samples() {
+        let mut cursor = Cursor::new(Vec::new());
+        let mut writer = WavWriter::new(&mut cursor, 2, 48000).unwrap();
+        writer.write_samples(&[0.0, 0.5, -0.5, 1.0]).unwrap();
+        drop(writer);
+        let bytes = cursor.into_inner();
+
+        assert_eq!(bytes.len(), 44 + 16);
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(bytes[4..8].try_into().unwrap()), 36 + 16);
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!(u16::from_le_bytes(bytes[20..22].try_into().unwrap()), 3);
+        assert_eq!(u16::from_le_bytes(bytes[22..24].try_into().unwrap()), 2);
+        assert_eq!(u32::from_le_bytes(bytes[24..28].try_into().unwrap()), 48000);
+        assert_eq!(u32::from_le_bytes(bytes[28..32].try_into().unwrap()), 48000 * 8);
+        assert_eq!(u16::from_le_bytes(bytes[32..34].try_into().unwrap()), 8);
+        assert_eq!(u16::from_le_bytes(bytes[34..36].try_into().unwrap()), 32);
+        assert_eq!(&bytes[36..40], b"data");
+        assert_eq!(u32::from_le_bytes(bytes[40..44].try_into().unwrap()), 16);
+        assert_eq!(f32::from_le_bytes(bytes[48..52].try_into().unwrap()), 0.5);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn records_mixed_sounds_into_wav_files() {
+        // 10ms of a constant stereo signal, half volume left and a quarter inverted right.
+        let frames = 441;
+        let data: Vec<u8> = (0..frames)
+            .flat_map(|_| [16384i16.to_le_bytes(), (-8192i16).to_le_bytes()])
+            .flatten()
+            .collect();
+        let sound = swf::Sound {
+            id: 1,
+            format: swf::SoundFormat {
+                compression: swf::AudioCompression::Uncompressed,
+                sample_rate: 44100,
+                is_stereo: true,
+                is_16_bit: true,
+            },
+            num_samples: frames as u32,
+            data: &data,
+        };
+        let path = std::env::temp_dir().join(format!("ruffle-wav-sink-{}.wav", std::process::id()));
+        let mut audio = MixerAudioBackend::new(Box::new(WavFileSink::new(&path).unwrap())).unwrap();
+        let handle = audio.register_sound(&sound).unwrap();
+        audio.play();
+        audio
+            .start_sound(handle, &swf::SoundInfo {
+                event: swf::SoundEvent::Event,
+                in_sample: None,
+                out_sample: None,
+                num_loops: 1,
+                envelope: None,
+            })
+            .unwrap();
+        // Ticks decide what gets written, wall clock polling doesn't.
+        audio.poll(Instant::now() + Duration::from_secs(1));
+        audio.on_tick(Duration::from_millis(20));
+        drop(audio);
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let samples: Vec<f32> = bytes[44..]
+            .chunks_exact(4)
+            .map(|it| f32::from_le_bytes(it.try_into().unwrap()))
+            .collect();
+        assert_eq!(u32::from_le_bytes(bytes[40..44].try_into().unwrap()) as usize, samples.len() * 4);
+        assert_eq!(samples.len(), 882 * 2);
+        let (sound, silence) = samples.split_at(frames * 2);
+        // Resampling may smear the edge by a frame, the rest must come through unchanged.
+        assert!(sound[..(frames - 1) * 2].chunks(2).all(|frame| frame == [0.5, -0.25]));
+        assert!(silence[2..].iter().all(|sample| *sample == 0.0));
+    }
+}
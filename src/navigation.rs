@@ -0,0 +1,138 @@
+use std::collections::HashSet;
+
+use ndk::event::Keycode;
+use ruffle_core::events::KeyDescriptor;
+
+use crate::{
+    input::{KeyAction, key_descriptor},
+    repeat::BindingMode,
+};
+
+/// What a navigation key does instead of its own key.
+#[derive(PartialEq, Debug)]
+pub enum NavAction {
+    /// Pressed and released right away, like the Tab that shows the focus highlight.
+    Tap(KeyDescriptor),
+    /// Held through the key repeater like any other key.
+    Press(KeyDescriptor, BindingMode),
+    /// Releases whatever the press turned into.
+    Release,
+}
+
+/// Drives Ruffle's keyboard focus with the d-pad. The first press shows the focus
+/// highlight with Tab, after that arrows move focus to the nearest object in that
+/// direction and the center key presses the focused object.
+#[derive(Default)]
+pub struct FocusNavigator {
+    pub enabled: bool,
+    /// Whether the movie is showing the focus highlight.
+    engaged: bool,
+    /// Navigation keys pressed through the navigator, so they are released through it too.
+    pressed: HashSet<i32>,
+}
+
+impl FocusNavigator {
+    /// What to do instead of `keycode`, `None` if it is not a navigation key. A focused
+    /// text field gets the d-pad as it is, to move the caret and submit.
+    pub fn translate(&mut self, keycode: Keycode, action: KeyAction, text_focused: bool) -> Option<NavAction> {
+        if !self.enabled {
+            return None;
+        }
+        let target = match keycode {
+            Keycode::DpadUp | Keycode::DpadDown | Keycode::DpadLeft | Keycode::DpadRight => keycode,
+            Keycode::DpadCenter => Keycode::Enter,
+            _ => return None,
+        };
+        let code: i32 = keycode.into();
+        match action {
+            KeyAction::Up => self.pressed.remove(&code).then_some(NavAction::Release),
+            KeyAction::Down if text_focused => None,
+            KeyAction::Down => {
+                let descriptor = key_descriptor(target, true)?;
+                self.pressed.insert(code);
+                if !self.engaged {
+                    self.engaged = true;
+                    return Some(NavAction::Tap(key_descriptor(Keycode::Tab, true)?));
+                }
+                // Holding a direction keeps moving, holding the center key clicks once.
+                let mode = if keycode == Keycode::DpadCenter {
+                    BindingMode::Normal
+                } else {
+                    BindingMode::Repeat
+                };
+                Some(NavAction::Press(descriptor, mode))
+            }
+        }
+    }
+
+    /// Ruffle hides the highlight once the mouse is used.
+    pub fn disengage(&mut self) {
+        self.engaged = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ruffle_core::events::{LogicalKey, NamedKey};
+
+    use super::*;
+
+    fn navigator() -> FocusNavigator {
+        FocusNavigator {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    fn logical_key(action: Option<NavAction>) -> Option<(LogicalKey, Option<BindingMode>)> {
+        match action? {
+            NavAction::Tap(descriptor) => Some((descriptor.logical_key, None)),
+            NavAction::Press(descriptor, mode) => Some((descriptor.logical_key, Some(mode))),
+            NavAction::Release => None,
+        }
+    }
+
+    #[test]
+    fn first_press_shows_focus_highlight() {
+        let mut navigator = navigator();
+        assert_eq!(
+            logical_key(navigator.translate(Keycode::DpadDown, KeyAction::Down, false)),
+            Some((LogicalKey::Named(NamedKey::Tab), None))
+        );
+        assert_eq!(navigator.translate(Keycode::DpadDown, KeyAction::Up, false), Some(NavAction::Release));
+        assert_eq!(
+            logical_key(navigator.translate(Keycode::DpadDown, KeyAction::Down, false)),
+            Some((LogicalKey::Named(NamedKey::ArrowDown), Some(BindingMode::Repeat)))
+        );
+    }
+
+    #[test]
+    fn center_presses_focused_object_once() {
+        let mut navigator = navigator();
+        navigator.translate(Keycode::DpadCenter, KeyAction::Down, false);
+        navigator.translate(Keycode::DpadCenter, KeyAction::Up, false);
+        assert_eq!(
+            logical_key(navigator.translate(Keycode::DpadCenter, KeyAction::Down, false)),
+            Some((LogicalKey::Named(NamedKey::Enter), Some(BindingMode::Normal)))
+        );
+        assert_eq!(navigator.translate(Keycode::DpadCenter, KeyAction::Up, false), Some(NavAction::Release));
+    }
+
+    #[test]
+    fn text_fields_get_the_dpad() {
+        let mut navigator = navigator();
+        navigator.translate(Keycode::DpadUp, KeyAction::Down, false);
+        assert!(navigator.translate(Keycode::DpadLeft, KeyAction::Down, true).is_none());
+        assert!(navigator.translate(Keycode::DpadLeft, KeyAction::Up, true).is_none());
+        // Released through the navigator when pressed through it, focus changed or not.
+        assert_eq!(navigator.translate(Keycode::DpadUp, KeyAction::Up, true), Some(NavAction::Release));
+    }
+
+    #[test]
+    fn passes_keys_through_when_disabled() {
+        let mut navigator = FocusNavigator::default();
+        assert!(navigator.translate(Keycode::DpadUp, KeyAction::Down, false).is_none());
+        navigator.enabled = true;
+        assert!(navigator.translate(Keycode::ButtonA, KeyAction::Down, false).is_none());
+    }
+}
@@ -0,0 +1,180 @@
+use std::{rc::Rc, time::Duration};
+
+use async_channel::{Receiver, Sender};
+use indexmap::IndexMap;
+use jni::objects::JValue;
+use log::{error, info, warn};
+use ruffle_core::{
+    backend::navigator::{
+        ErrorResponse, NavigationMethod, NavigatorBackend, NullNavigatorBackend, OwnedFuture, Request,
+        SuccessResponse,
+    },
+    loader::Error,
+    socket::{SocketAction, SocketHandle},
+};
+use tokio::task::LocalSet;
+use url::{ParseError, Url};
+
+use crate::ui::JavaHost;
+
+/// What happens when the movie asks to open a web page, e.g. with `navigateToURL`.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum OpenUrlMode {
+    /// The host opens the page right away.
+    Allow,
+    /// The host asks the user first.
+    Confirm,
+    Deny,
+}
+
+impl From<&str> for OpenUrlMode {
+    fn from(mode: &str) -> Self {
+        match mode {
+            "allow" => OpenUrlMode::Allow,
+            "deny" => OpenUrlMode::Deny,
+            _ => OpenUrlMode::Confirm,
+        }
+    }
+}
+
+/// Hands page navigation to the host, loading and sockets behave like the null backend.
+/// Futures run on `tasks`, which the worker loop drives while it does not hold the player.
+pub struct AndroidNavigatorBackend {
+    inner: NullNavigatorBackend,
+    host: JavaHost,
+    open_url_mode: OpenUrlMode,
+    tasks: Rc<LocalSet>,
+}
+
+impl AndroidNavigatorBackend {
+    pub fn new(host: JavaHost, open_url_mode: OpenUrlMode, tasks: Rc<LocalSet>) -> Self {
+        Self {
+            inner: NullNavigatorBackend::new(),
+            host,
+            open_url_mode,
+            tasks,
+        }
+    }
+}
+
+impl NavigatorBackend for AndroidNavigatorBackend {
+    fn navigate_to_url(
+        &self,
+        url: &str,
+        _target: &str,
+        vars_method: Option<(NavigationMethod, IndexMap<String, String>)>,
+    ) {
+        if self.open_url_mode == OpenUrlMode::Deny {
+            info!("Refused to open {url}, opening URLs is denied.");
+            return;
+        }
+        let url = match self.resolve_url(url) {
+            Ok(url) => url,
+            Err(e) => {
+                warn!("Refused to open {url}! {e}");
+                return;
+            }
+        };
+        let Some(url) = navigation_url(url, vars_method) else {
+            return;
+        };
+        let confirm = self.open_url_mode == OpenUrlMode::Confirm;
+        let result = self.host.with_env(|env| {
+            let url = env.new_string(url.as_str())?;
+            env.call_method(
+                self.host.thiz(),
+                "onOpenUrl",
+                "(Ljava/lang/String;Z)V",
+                &[JValue::Object(&url), JValue::from(confirm)],
+            )
+            .map(|_| ())
+        });
+        if let Err(e) = result {
+            error!("Failed to open {url}! {e}");
+        }
+    }
+
+    fn fetch(&self, request: Request) -> OwnedFuture<Box<dyn SuccessResponse>, ErrorResponse> {
+        self.inner.fetch(request)
+    }
+
+    fn resolve_url(&self, url: &str) -> Result<Url, ParseError> {
+        self.inner.resolve_url(url)
+    }
+
+    fn spawn_future(&mut self, future: OwnedFuture<(), Error>) {
+        self.tasks.spawn_local(async move {
+            if let Err(e) = future.await {
+                error!("Async task failed! {e}");
+            }
+        });
+    }
+
+    fn pre_process_url(&self, url: Url) -> Url {
+        self.inner.pre_process_url(url)
+    }
+
+    fn connect_socket(
+        &mut self,
+        host: String,
+        port: u16,
+        timeout: Duration,
+        handle: SocketHandle,
+        receiver: Receiver<Vec<u8>>,
+        sender: Sender<SocketAction>,
+    ) {
+        self.inner
+            .connect_socket(host, port, timeout, handle, receiver, sender)
+    }
+}
+
+/// The page a browser should open, `None` for URLs that must not leave the player.
+/// Variables sent with GET go into the query, a browser cannot be handed a POST body.
+fn navigation_url(
+    mut url: Url,
+    vars_method: Option<(NavigationMethod, IndexMap<String, String>)>,
+) -> Option<Url> {
+    if !matches!(url.scheme(), "http" | "https" | "mailto") {
+        warn!("Refused to open {url}, scheme not allowed.");
+        return None;
+    }
+    match vars_method {
+        Some((NavigationMethod::Get, vars)) if !vars.is_empty() => {
+            url.query_pairs_mut().extend_pairs(vars.iter());
+        }
+        Some((NavigationMethod::Post, vars)) if !vars.is_empty() => {
+            warn!("Opening {url} without its POST variables.");
+        }
+        _ => (),
+    }
+    Some(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    #[test]
+    fn appends_get_variables() {
+        let vars = IndexMap::from([("level".to_string(), "2".to_string()), ("name".to_string(), "a b".to_string())]);
+        assert_eq!(
+            navigation_url(url("https://example.com/play?x=1"), Some((NavigationMethod::Get, vars.clone()))),
+            Some(url("https://example.com/play?x=1&level=2&name=a+b"))
+        );
+        assert_eq!(
+            navigation_url(url("https://example.com/play"), Some((NavigationMethod::Post, vars))),
+            Some(url("https://example.com/play"))
+        );
+    }
+
+    #[test]
+    fn refuses_non_web_schemes() {
+        assert_eq!(navigation_url(url("javascript:alert(1)"), None), None);
+        assert_eq!(navigation_url(url("file:///sdcard/save.sol"), None), None);
+        assert!(navigation_url(url("mailto:dev@example.com"), None).is_some());
+    }
+}
@@ -0,0 +1,446 @@
+use std::{
+    error::Error,
+    fmt::{self, Debug},
+    sync::{Arc, Mutex},
+};
+
+use ruffle_render_wgpu::{
+    backend::{WgpuRenderBackend, request_adapter_and_device},
+    descriptors::Descriptors,
+    target::{RenderTarget, RenderTargetFrame, SwapChainTarget, SwapChainTargetFrame},
+    wgpu,
+};
+
+/// Glyph cells are 5x7 pixels, with a column and two rows of spacing.
+const GLYPH_WIDTH: u32 = 5;
+const GLYPH_HEIGHT: u32 = 7;
+const CELL_WIDTH: u32 = GLYPH_WIDTH + 1;
+const CELL_HEIGHT: u32 = GLYPH_HEIGHT + 2;
+const PADDING: u32 = 4;
+const BACKGROUND: [u8; 4] = [0, 0, 0, 160];
+const FOREGROUND: [u8; 4] = [255, 255, 255, 255];
+
+/// Just the characters `StatsSnapshot::overlay_text` uses, anything else is left blank.
+/// Each row is 5 bits, the highest one being the leftmost pixel.
+const GLYPHS: &[(char, [u8; 7])] = &[
+    ('0', [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110]),
+    ('1', [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('2', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111]),
+    ('3', [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110]),
+    ('4', [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010]),
+    ('5', [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110]),
+    ('6', [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110]),
+    ('7', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000]),
+    ('8', [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110]),
+    ('9', [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100]),
+    ('.', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100]),
+    ('(', [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010]),
+    (')', [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000]),
+    ('/', [0b00000, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b00000]),
+    ('a', [0b00000, 0b00000, 0b01110, 0b00001, 0b01111, 0b10001, 0b01111]),
+    ('c', [0b00000, 0b00000, 0b01110, 0b10000, 0b10000, 0b10001, 0b01110]),
+    ('d', [0b00001, 0b00001, 0b01101, 0b10011, 0b10001, 0b10001, 0b01111]),
+    ('e', [0b00000, 0b00000, 0b01110, 0b10001, 0b11111, 0b10000, 0b01110]),
+    ('f', [0b00110, 0b01001, 0b01000, 0b11100, 0b01000, 0b01000, 0b01000]),
+    ('g', [0b00000, 0b01111, 0b10001, 0b10001, 0b01111, 0b00001, 0b01110]),
+    ('i', [0b00100, 0b00000, 0b01100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('k', [0b10000, 0b10000, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010]),
+    ('m', [0b00000, 0b00000, 0b11010, 0b10101, 0b10101, 0b10001, 0b10001]),
+    ('n', [0b00000, 0b00000, 0b10110, 0b11001, 0b10001, 0b10001, 0b10001]),
+    ('o', [0b00000, 0b00000, 0b01110, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('p', [0b00000, 0b00000, 0b11110, 0b10001, 0b11110, 0b10000, 0b10000]),
+    ('r', [0b00000, 0b00000, 0b10110, 0b11001, 0b10000, 0b10000, 0b10000]),
+    ('s', [0b00000, 0b00000, 0b01110, 0b10000, 0b01110, 0b00001, 0b11110]),
+    ('t', [0b01000, 0b01000, 0b11100, 0b01000, 0b01000, 0b01001, 0b00110]),
+    ('u', [0b00000, 0b00000, 0b10001, 0b10001, 0b10001, 0b10011, 0b01101]),
+    ('x', [0b00000, 0b00000, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001]),
+];
+
+const SHADER: &str = r#"
+@group(0) @binding(0) var overlay: texture_2d<f32>;
+
+// One triangle covering the target, the scissor rect limits it to the overlay.
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    return textureLoad(overlay, vec2<i32>(position.xy), 0);
+}
+"#;
+
+/// RGBA pixels of the overlay, drawn at the top left corner of the frame.
+#[derive(Clone, PartialEq, Debug)]
+pub struct OverlayImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl OverlayImage {
+    /// Draws `text` with the built-in font, every font pixel covering `scale` by `scale` pixels.
+    pub fn from_text(text: &str, scale: u32) -> Self {
+        let scale = scale.max(1);
+        let lines = text.lines().collect::<Vec<_>>();
+        let columns = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0) as u32;
+        let width = (PADDING * 2 + (columns * CELL_WIDTH).saturating_sub(1)) * scale;
+        let height = (PADDING * 2 + (lines.len() as u32 * CELL_HEIGHT).saturating_sub(2)) * scale;
+        let mut pixels = BACKGROUND.repeat((width * height) as usize);
+        for (row, line) in lines.iter().enumerate() {
+            for (column, c) in line.chars().enumerate() {
+                let Some((_, glyph)) = GLYPHS.iter().find(|(it, _)| *it == c) else {
+                    continue;
+                };
+                let left = PADDING + column as u32 * CELL_WIDTH;
+                let top = PADDING + row as u32 * CELL_HEIGHT;
+                for (y, bits) in glyph.iter().enumerate() {
+                    for x in 0..GLYPH_WIDTH {
+                        if bits & (1 << (GLYPH_WIDTH - 1 - x)) == 0 {
+                            continue;
+                        }
+                        for dy in 0..scale {
+                            let py = (top + y as u32) * scale + dy;
+                            let start = ((py * width + (left + x) * scale) * 4) as usize;
+                            for pixel in pixels[start..start + (scale * 4) as usize].chunks_mut(4) {
+                                pixel.copy_from_slice(&FOREGROUND);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Self { width, height, pixels }
+    }
+}
+
+#[derive(Default)]
+struct Shared {
+    image: Option<OverlayImage>,
+    /// Bumped for every new image, so the target uploads each one once.
+    version: u64,
+    /// A swap chain for a new window, picked up before the next frame.
+    swap_chain: Option<SwapChainTarget>,
+}
+
+/// What the worker loop hands to `OverlayTarget`.
+#[derive(Clone, Default)]
+pub struct OverlayHandle(Arc<Mutex<Shared>>);
+
+impl OverlayHandle {
+    pub fn show(&self, image: Option<OverlayImage>) {
+        let mut shared = self.0.lock().unwrap();
+        shared.image = image;
+        shared.version += 1;
+    }
+
+    /// Moves rendering to a new window, the renderer keeps its device and resources.
+    ///
+    /// # Safety
+    ///
+    /// `window` must stay valid until the surface is replaced again or the renderer dropped.
+    pub unsafe fn recreate_surface(
+        &self,
+        descriptors: &Descriptors,
+        window: wgpu::SurfaceTargetUnsafe,
+        size: (u32, u32),
+    ) -> Result<(), Box<dyn Error>> {
+        let surface = unsafe { descriptors.wgpu_instance.create_surface_unsafe(window)? };
+        let swap_chain = SwapChainTarget::new(surface, &descriptors.adapter, size, &descriptors.device);
+        self.0.lock().unwrap().swap_chain = Some(swap_chain);
+        Ok(())
+    }
+}
+
+struct Pipeline {
+    format: wgpu::TextureFormat,
+    layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+}
+
+struct Uploaded {
+    version: u64,
+    width: u32,
+    height: u32,
+    bind_group: Option<wgpu::BindGroup>,
+}
+
+/// Presents to the window like `SwapChainTarget`, with the stats overlay drawn over
+/// each frame in a render pass of its own, after Ruffle's.
+pub struct OverlayTarget {
+    swap_chain: SwapChainTarget,
+    handle: OverlayHandle,
+    pipeline: Mutex<Option<Pipeline>>,
+    uploaded: Mutex<Uploaded>,
+}
+
+impl Debug for OverlayTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OverlayTarget")
+            .field("swap_chain", &self.swap_chain)
+            .finish_non_exhaustive()
+    }
+}
+
+impl OverlayTarget {
+    pub fn new(swap_chain: SwapChainTarget, handle: OverlayHandle) -> Self {
+        Self {
+            swap_chain,
+            handle,
+            pipeline: Mutex::new(None),
+            uploaded: Mutex::new(Uploaded {
+                version: 0,
+                width: 0,
+                height: 0,
+                bind_group: None,
+            }),
+        }
+    }
+
+    fn take_new_swap_chain(&mut self) {
+        if let Some(swap_chain) = self.handle.0.lock().unwrap().swap_chain.take() {
+            self.swap_chain = swap_chain;
+        }
+    }
+
+    /// Uploads the latest image if it changed, `None` while there is nothing to draw.
+    fn bind_group(&self, device: &wgpu::Device, queue: &wgpu::Queue, layout: &wgpu::BindGroupLayout) -> Option<(wgpu::BindGroup, u32, u32)> {
+        let shared = self.handle.0.lock().unwrap();
+        let mut uploaded = self.uploaded.lock().unwrap();
+        if uploaded.version != shared.version {
+            uploaded.version = shared.version;
+            uploaded.bind_group = shared.image.as_ref().map(|image| {
+                let size = wgpu::Extent3d {
+                    width: image.width,
+                    height: image.height,
+                    depth_or_array_layers: 1,
+                };
+                let texture = device.create_texture(&wgpu::TextureDescriptor {
+                    label: Some("Stats overlay"),
+                    size,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: wgpu::TextureFormat::Rgba8Unorm,
+                    usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                    view_formats: &[],
+                });
+                queue.write_texture(
+                    wgpu::TexelCopyTextureInfo {
+                        texture: &texture,
+                        mip_level: 0,
+                        origin: wgpu::Origin3d::ZERO,
+                        aspect: wgpu::TextureAspect::All,
+                    },
+                    &image.pixels,
+                    wgpu::TexelCopyBufferLayout {
+                        offset: 0,
+                        bytes_per_row: Some(image.width * 4),
+                        rows_per_image: None,
+                    },
+                    size,
+                );
+                let view = texture.create_view(&Default::default());
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Stats overlay"),
+                    layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&view),
+                    }],
+                })
+            });
+            if let Some(image) = &shared.image {
+                uploaded.width = image.width;
+                uploaded.height = image.height;
+            }
+        }
+        let bind_group = uploaded.bind_group.clone()?;
+        Some((bind_group, uploaded.width, uploaded.height))
+    }
+
+    fn draw(&self, device: &wgpu::Device, queue: &wgpu::Queue, frame: &SwapChainTargetFrame) -> Option<wgpu::CommandBuffer> {
+        let format = self.swap_chain.format();
+        let mut pipeline = self.pipeline.lock().unwrap();
+        if pipeline.as_ref().is_none_or(|it| it.format != format) {
+            *pipeline = Some(create_pipeline(device, format));
+        }
+        let pipeline = pipeline.as_ref()?;
+        let (bind_group, width, height) = self.bind_group(device, queue, &pipeline.layout)?;
+        let width = width.min(self.swap_chain.width());
+        let height = height.min(self.swap_chain.height());
+        if width == 0 || height == 0 {
+            return None;
+        }
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Stats overlay"),
+        });
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Stats overlay"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: frame.view(),
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                ..Default::default()
+            });
+            pass.set_pipeline(&pipeline.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.set_scissor_rect(0, 0, width, height);
+            pass.draw(0..3, 0..1);
+        }
+        Some(encoder.finish())
+    }
+}
+
+fn create_pipeline(device: &wgpu::Device, format: wgpu::TextureFormat) -> Pipeline {
+    let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Stats overlay"),
+        source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+    });
+    let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Stats overlay"),
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        }],
+    });
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Stats overlay"),
+        bind_group_layouts: &[&layout],
+        push_constant_ranges: &[],
+    });
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Stats overlay"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &module,
+            entry_point: Some("vs_main"),
+            compilation_options: Default::default(),
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &module,
+            entry_point: Some("fs_main"),
+            compilation_options: Default::default(),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: Default::default(),
+        depth_stencil: None,
+        multisample: Default::default(),
+        multiview: None,
+        cache: None,
+    });
+    Pipeline { format, layout, pipeline }
+}
+
+impl RenderTarget for OverlayTarget {
+    type Frame = SwapChainTargetFrame;
+
+    fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        self.take_new_swap_chain();
+        self.swap_chain.resize(device, width, height)
+    }
+
+    fn format(&self) -> wgpu::TextureFormat {
+        self.swap_chain.format()
+    }
+
+    fn width(&self) -> u32 {
+        self.swap_chain.width()
+    }
+
+    fn height(&self) -> u32 {
+        self.swap_chain.height()
+    }
+
+    fn get_next_texture(&mut self) -> Result<Self::Frame, wgpu::SurfaceError> {
+        self.take_new_swap_chain();
+        self.swap_chain.get_next_texture()
+    }
+
+    fn submit<I: IntoIterator<Item = wgpu::CommandBuffer>>(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        command_buffers: I,
+        frame: Self::Frame,
+    ) -> wgpu::SubmissionIndex {
+        let overlay = self.draw(device, queue, &frame);
+        self.swap_chain
+            .submit(device, queue, command_buffers.into_iter().chain(overlay), frame)
+    }
+}
+
+/// Same as `WgpuRenderBackend::for_window_unsafe`, but rendering through an `OverlayTarget`.
+///
+/// # Safety
+///
+/// `window` must outlive the renderer, or the surface made from it must be replaced first.
+pub async unsafe fn create_renderer(
+    window: wgpu::SurfaceTargetUnsafe,
+    size: (u32, u32),
+    backends: wgpu::Backends,
+    power_preference: wgpu::PowerPreference,
+    handle: OverlayHandle,
+) -> Result<WgpuRenderBackend<OverlayTarget>, Box<dyn Error>> {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends,
+        ..Default::default()
+    });
+    let surface = unsafe { instance.create_surface_unsafe(window)? };
+    let (adapter, device, queue) =
+        request_adapter_and_device(backends, &instance, Some(&surface), power_preference).await?;
+    let descriptors = Descriptors::new(instance, adapter, device, queue);
+    let swap_chain = SwapChainTarget::new(surface, &descriptors.adapter, size, &descriptors.device);
+    Ok(WgpuRenderBackend::new(Arc::new(descriptors), OverlayTarget::new(swap_chain, handle))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(image: &OverlayImage, x: u32, y: u32) -> &[u8] {
+        let start = ((y * image.width + x) * 4) as usize;
+        &image.pixels[start..start + 4]
+    }
+
+    #[test]
+    fn sizes_image_to_the_longest_line() {
+        let image = OverlayImage::from_text("12\n1", 1);
+        assert_eq!((image.width, image.height), (PADDING * 2 + 11, PADDING * 2 + 16));
+        assert_eq!(image.pixels.len(), (image.width * image.height * 4) as usize);
+        let image = OverlayImage::from_text("12\n1", 3);
+        assert_eq!((image.width, image.height), ((PADDING * 2 + 11) * 3, (PADDING * 2 + 16) * 3));
+    }
+
+    #[test]
+    fn draws_glyphs_over_the_background() {
+        let image = OverlayImage::from_text("1 ?", 2);
+        // The top of the "1" is its third column.
+        assert_eq!(pixel(&image, (PADDING + 2) * 2, PADDING * 2), FOREGROUND);
+        assert_eq!(pixel(&image, (PADDING + 2) * 2 + 1, PADDING * 2 + 1), FOREGROUND);
+        assert_eq!(pixel(&image, PADDING * 2, PADDING * 2), BACKGROUND);
+        // Spaces and unknown characters stay blank.
+        let blank = (0..image.height)
+            .all(|y| ((PADDING + CELL_WIDTH) * 2..image.width).all(|x| pixel(&image, x, y) == BACKGROUND));
+        assert!(blank);
+    }
+}
@@ -0,0 +1,224 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use ruffle_core::events::KeyDescriptor;
+
+use crate::input::KeyAction;
+
+/// How a remapped key drives its targets, written as `[repeat]`, `[turbo]` or `[toggle]`
+/// after a binding.
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub enum BindingMode {
+    #[default]
+    Normal,
+    /// Held targets are pressed again at the repeat rate, like a keyboard.
+    Repeat,
+    /// Held targets are pressed and released at the turbo rate.
+    Turbo,
+    /// One press holds the targets down, the next one releases them.
+    Toggle,
+}
+
+impl TryFrom<&str> for BindingMode {
+    type Error = String;
+
+    fn try_from(mode: &str) -> Result<Self, Self::Error> {
+        match mode {
+            "repeat" => Ok(BindingMode::Repeat),
+            "turbo" => Ok(BindingMode::Turbo),
+            "toggle" => Ok(BindingMode::Toggle),
+            _ => Err(format!("Unknown binding mode \"{mode}\"")),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RepeatConfig {
+    pub delay: Duration,
+    pub interval: Duration,
+    /// Presses per second in turbo mode.
+    pub turbo_rate: f64,
+}
+
+impl Default for RepeatConfig {
+    fn default() -> Self {
+        Self {
+            delay: Duration::from_millis(400),
+            interval: Duration::from_millis(50),
+            turbo_rate: 10.0,
+        }
+    }
+}
+
+impl RepeatConfig {
+    /// Time between a turbo press and its release, and between the release and the next press.
+    fn turbo_half_period(&self) -> Duration {
+        Duration::from_secs_f64(0.5 / self.turbo_rate.max(0.5))
+    }
+}
+
+struct HeldKey {
+    descriptors: Vec<KeyDescriptor>,
+    mode: BindingMode,
+    is_down: bool,
+    /// A toggled key whose source has been let go of, waiting for the next press.
+    latched: bool,
+    next: Option<Instant>,
+}
+
+/// The keys the movie currently sees as held, keyed by device and source keycode.
+/// What each was pressed as is kept, so swapping profiles mid-press cannot leave keys stuck.
+pub struct KeyRepeater {
+    pub config: RepeatConfig,
+    held: HashMap<(i32, i32), HeldKey>,
+}
+
+impl KeyRepeater {
+    pub fn new(config: RepeatConfig) -> Self {
+        Self {
+            config,
+            held: HashMap::new(),
+        }
+    }
+
+    pub fn press(
+        &mut self,
+        source: (i32, i32),
+        descriptors: Vec<KeyDescriptor>,
+        mode: BindingMode,
+        now: Instant,
+    ) -> Vec<(KeyDescriptor, KeyAction)> {
+        if let Some(held) = self.held.get_mut(&source) {
+            if !held.latched {
+                return Vec::new();
+            }
+            // Released now, but the source still has to be let go of.
+            held.mode = BindingMode::Normal;
+            held.is_down = false;
+            held.latched = false;
+            return transitions(&held.descriptors, KeyAction::Up);
+        }
+        let next = match mode {
+            BindingMode::Repeat => Some(now + self.config.delay),
+            BindingMode::Turbo => Some(now + self.config.turbo_half_period()),
+            BindingMode::Normal | BindingMode::Toggle => None,
+        };
+        let events = transitions(&descriptors, KeyAction::Down);
+        self.held.insert(
+            source,
+            HeldKey {
+                descriptors,
+                mode,
+                is_down: true,
+                latched: false,
+                next,
+            },
+        );
+        events
+    }
+
+    /// `None` if the source was never pressed, so the caller has to work out what to release.
+    pub fn release(&mut self, source: (i32, i32)) -> Option<Vec<(KeyDescriptor, KeyAction)>> {
+        let held = self.held.get_mut(&source)?;
+        if held.mode == BindingMode::Toggle {
+            held.latched = true;
+            return Some(Vec::new());
+        }
+        let held = self.held.remove(&source).unwrap();
+        if held.is_down {
+            Some(transitions(&held.descriptors, KeyAction::Up))
+        } else {
+            Some(Vec::new())
+        }
+    }
+
+    pub fn poll(&mut self, now: Instant) -> Vec<(KeyDescriptor, KeyAction)> {
+        let mut events = Vec::new();
+        for held in self.held.values_mut() {
+            let Some(next) = held.next else {
+                continue;
+            };
+            if next > now {
+                continue;
+            }
+            match held.mode {
+                BindingMode::Repeat => {
+                    events.extend(transitions(&held.descriptors, KeyAction::Down));
+                    held.next = Some(now + self.config.interval);
+                }
+                BindingMode::Turbo => {
+                    held.is_down = !held.is_down;
+                    events.extend(transitions(&held.descriptors, KeyAction::from(held.is_down)));
+                    held.next = Some(now + self.config.turbo_half_period());
+                }
+                BindingMode::Normal | BindingMode::Toggle => held.next = None,
+            }
+        }
+        events
+    }
+}
+
+fn transitions(descriptors: &[KeyDescriptor], action: KeyAction) -> Vec<(KeyDescriptor, KeyAction)> {
+    descriptors.iter().map(|descriptor| (*descriptor, action)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ruffle_core::events::{KeyLocation, LogicalKey, PhysicalKey};
+
+    use super::*;
+
+    const SPACE: KeyDescriptor = KeyDescriptor {
+        physical_key: PhysicalKey::Space,
+        logical_key: LogicalKey::Character(' '),
+        key_location: KeyLocation::Standard,
+    };
+
+    fn actions(events: Vec<(KeyDescriptor, KeyAction)>) -> Vec<KeyAction> {
+        events.into_iter().map(|(_, action)| action).collect()
+    }
+
+    #[test]
+    fn repeat_presses_again_after_delay() {
+        let now = Instant::now();
+        let mut repeater = KeyRepeater::new(RepeatConfig::default());
+        assert_eq!(actions(repeater.press((0, 96), vec![SPACE], BindingMode::Repeat, now)), [KeyAction::Down]);
+        assert!(repeater.poll(now + Duration::from_millis(399)).is_empty());
+        assert_eq!(actions(repeater.poll(now + Duration::from_millis(400))), [KeyAction::Down]);
+        assert!(repeater.poll(now + Duration::from_millis(420)).is_empty());
+        assert_eq!(actions(repeater.poll(now + Duration::from_millis(450))), [KeyAction::Down]);
+        assert_eq!(actions(repeater.release((0, 96)).unwrap()), [KeyAction::Up]);
+        assert!(repeater.poll(now + Duration::from_secs(1)).is_empty());
+    }
+
+    #[test]
+    fn turbo_alternates_and_releases_once() {
+        let now = Instant::now();
+        let mut repeater = KeyRepeater::new(RepeatConfig::default());
+        repeater.press((0, 96), vec![SPACE], BindingMode::Turbo, now);
+        assert_eq!(actions(repeater.poll(now + Duration::from_millis(50))), [KeyAction::Up]);
+        assert_eq!(actions(repeater.poll(now + Duration::from_millis(100))), [KeyAction::Down]);
+        assert_eq!(actions(repeater.poll(now + Duration::from_millis(150))), [KeyAction::Up]);
+        // Already up, so letting go sends nothing.
+        assert!(repeater.release((0, 96)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn toggle_holds_until_pressed_again() {
+        let now = Instant::now();
+        let mut repeater = KeyRepeater::new(RepeatConfig::default());
+        assert_eq!(actions(repeater.press((0, 96), vec![SPACE], BindingMode::Toggle, now)), [KeyAction::Down]);
+        assert!(repeater.release((0, 96)).unwrap().is_empty());
+        assert_eq!(actions(repeater.press((0, 96), vec![SPACE], BindingMode::Toggle, now)), [KeyAction::Up]);
+        assert!(repeater.release((0, 96)).unwrap().is_empty());
+        assert_eq!(repeater.release((0, 96)), None);
+    }
+
+    #[test]
+    fn parses_binding_modes() {
+        assert_eq!(BindingMode::try_from("turbo"), Ok(BindingMode::Turbo));
+        assert!(BindingMode::try_from("mash").is_err());
+    }
+}
@@ -0,0 +1,398 @@
+use std::{
+    collections::VecDeque,
+    fs::{self, File},
+    io::{self, BufWriter, ErrorKind, Write},
+    path::Path,
+};
+
+use log::warn;
+use ndk::event::Keycode;
+
+use crate::{
+    ime::TextEvent,
+    input::{InputEvent, KeyAction, KeyEvent, PointerAction, PointerEvent, TouchAction, TouchEvent, TouchPointer},
+    stick::JoystickEvent,
+};
+
+const MAGIC: &[u8; 4] = b"RFIR";
+const VERSION: u8 = 1;
+
+const KIND_KEY: u8 = 0;
+const KIND_TOUCH: u8 = 1;
+const KIND_POINTER: u8 = 2;
+const KIND_JOYSTICK: u8 = 3;
+const KIND_TEXT_COMMIT: u8 = 4;
+const KIND_TEXT_COMPOSING: u8 = 5;
+const KIND_TEXT_DELETE: u8 = 6;
+
+/// An input as it reached the worker, stamped with the number of ticks run
+/// before it and the movie time those ticks added up to.
+#[derive(Clone, Debug)]
+pub struct InputRecord {
+    pub frame: u64,
+    pub time_us: u64,
+    pub event: InputEvent,
+}
+
+/// Writes inputs to a replay file. Frames and times are stored as deltas to
+/// the previous record and integers as varints, pointer positions keep the
+/// `f32` precision Android reports them in.
+pub struct InputRecorder<W: Write = BufWriter<File>> {
+    out: W,
+    frame: u64,
+    time_us: u64,
+}
+
+impl InputRecorder {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> InputRecorder<W> {
+    pub fn new(mut out: W) -> io::Result<Self> {
+        out.write_all(MAGIC)?;
+        out.write_all(&[VERSION])?;
+        Ok(Self {
+            out,
+            frame: 0,
+            time_us: 0,
+        })
+    }
+
+    pub fn record(&mut self, frame: u64, time_us: u64, event: &InputEvent) -> io::Result<()> {
+        let mut buf = Vec::new();
+        write_varint(&mut buf, frame.saturating_sub(self.frame));
+        write_varint(&mut buf, time_us.saturating_sub(self.time_us));
+        self.frame = frame;
+        self.time_us = time_us;
+        match event {
+            InputEvent::Key(event) => {
+                buf.push(KIND_KEY);
+                write_signed(&mut buf, event.key().into());
+                write_signed(&mut buf, event.action().into());
+                write_signed(&mut buf, event.device_id());
+                write_varint(&mut buf, event.meta_state() as u64);
+                write_varint(&mut buf, event.unicode_char() as u64);
+                write_signed(&mut buf, event.repeat_count());
+            }
+            InputEvent::Touch(event) => {
+                buf.push(KIND_TOUCH);
+                write_signed(&mut buf, event.action.into());
+                write_signed(&mut buf, event.pointer_id);
+                write_varint(&mut buf, event.pointers.len() as u64);
+                for pointer in &event.pointers {
+                    write_signed(&mut buf, pointer.id);
+                    write_f32(&mut buf, pointer.x as f32);
+                    write_f32(&mut buf, pointer.y as f32);
+                }
+            }
+            InputEvent::Pointer(event) => {
+                buf.push(KIND_POINTER);
+                write_signed(&mut buf, event.action.into());
+                write_f32(&mut buf, event.x as f32);
+                write_f32(&mut buf, event.y as f32);
+                write_signed(&mut buf, event.button_state);
+                write_f32(&mut buf, event.scroll as f32);
+            }
+            InputEvent::Joystick(event) => {
+                buf.push(KIND_JOYSTICK);
+                write_signed(&mut buf, event.device_id);
+                for axis in [event.left, event.right, event.hat] {
+                    write_f32(&mut buf, axis.0);
+                    write_f32(&mut buf, axis.1);
+                }
+            }
+            InputEvent::Text(TextEvent::Commit(text)) => {
+                buf.push(KIND_TEXT_COMMIT);
+                write_str(&mut buf, text);
+            }
+            InputEvent::Text(TextEvent::Composing(text)) => {
+                buf.push(KIND_TEXT_COMPOSING);
+                write_str(&mut buf, text);
+            }
+            InputEvent::Text(TextEvent::DeleteSurrounding(before, after)) => {
+                buf.push(KIND_TEXT_DELETE);
+                write_varint(&mut buf, *before as u64);
+                write_varint(&mut buf, *after as u64);
+            }
+        }
+        self.out.write_all(&buf)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// A loaded replay file, handing its inputs back out at the frames they were recorded at.
+pub struct InputReplay {
+    records: VecDeque<InputRecord>,
+}
+
+impl InputReplay {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::parse(&fs::read(path)?)
+    }
+
+    pub fn parse(data: &[u8]) -> io::Result<Self> {
+        let mut reader = Reader { data };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(invalid("not an input replay"));
+        }
+        let version = reader.byte()?;
+        if version != VERSION {
+            return Err(invalid(&format!("unsupported replay version {version}")));
+        }
+        let mut records = VecDeque::new();
+        let (mut frame, mut time_us) = (0u64, 0u64);
+        while !reader.data.is_empty() {
+            match read_record(&mut reader, frame, time_us) {
+                Ok(record) => {
+                    (frame, time_us) = (record.frame, record.time_us);
+                    records.push_back(record);
+                }
+                // A recording cut short by a crash or a kill still replays up to its last whole record.
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                    warn!("Input replay ends in a partial record at frame {frame}, ignoring it.");
+                    break;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(Self { records })
+    }
+
+    /// Inputs recorded once `frame` ticks had run, in their original order.
+    pub fn take_due(&mut self, frame: u64) -> Vec<InputRecord> {
+        let mut due = Vec::new();
+        while self.records.front().is_some_and(|record| record.frame <= frame) {
+            due.push(self.records.pop_front().unwrap());
+        }
+        due
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+/// Reads the record after the one at `frame` and `time_us`.
+fn read_record(reader: &mut Reader, frame: u64, time_us: u64) -> io::Result<InputRecord> {
+    let frame = frame
+        .checked_add(reader.varint()?)
+        .ok_or_else(|| invalid("frame counter overflow"))?;
+    let time_us = time_us
+        .checked_add(reader.varint()?)
+        .ok_or_else(|| invalid("movie time overflow"))?;
+    let event = match reader.byte()? {
+        KIND_KEY => InputEvent::Key(
+            KeyEvent::new(
+                Keycode::from(reader.signed()?),
+                KeyAction::from(reader.signed()?),
+                reader.signed()?,
+            )
+            .with_text(reader.varint()? as u32, reader.varint()? as u32)
+            .with_repeat_count(reader.signed()?),
+        ),
+        KIND_TOUCH => {
+            let action = TouchAction::from(reader.signed()?);
+            let pointer_id = reader.signed()?;
+            let count = reader.varint()?;
+            let mut pointers = Vec::new();
+            for _ in 0..count {
+                pointers.push(TouchPointer {
+                    id: reader.signed()?,
+                    x: reader.f32()? as f64,
+                    y: reader.f32()? as f64,
+                });
+            }
+            InputEvent::Touch(TouchEvent::new(action, pointer_id, pointers))
+        }
+        KIND_POINTER => InputEvent::Pointer(PointerEvent::new(
+            PointerAction::from(reader.signed()?),
+            reader.f32()? as f64,
+            reader.f32()? as f64,
+            reader.signed()?,
+            reader.f32()? as f64,
+        )),
+        KIND_JOYSTICK => InputEvent::Joystick(JoystickEvent {
+            device_id: reader.signed()?,
+            left: (reader.f32()?, reader.f32()?),
+            right: (reader.f32()?, reader.f32()?),
+            hat: (reader.f32()?, reader.f32()?),
+        }),
+        KIND_TEXT_COMMIT => InputEvent::Text(TextEvent::Commit(reader.string()?)),
+        KIND_TEXT_COMPOSING => InputEvent::Text(TextEvent::Composing(reader.string()?)),
+        KIND_TEXT_DELETE => InputEvent::Text(TextEvent::DeleteSurrounding(
+            reader.varint()? as usize,
+            reader.varint()? as usize,
+        )),
+        kind => return Err(invalid(&format!("unknown record kind {kind}"))),
+    };
+    Ok(InputRecord { frame, time_us, event })
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message.to_string())
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn write_signed(buf: &mut Vec<u8>, value: i32) {
+    write_varint(buf, ((value << 1) ^ (value >> 31)) as u32 as u64);
+}
+
+fn write_f32(buf: &mut Vec<u8>, value: f32) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_str(buf: &mut Vec<u8>, value: &str) {
+    write_varint(buf, value.len() as u64);
+    buf.extend_from_slice(value.as_bytes());
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.data.len() < len {
+            return Err(io::Error::from(ErrorKind::UnexpectedEof));
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn byte(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> io::Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid("varint too long"))
+    }
+
+    fn signed(&mut self) -> io::Result<i32> {
+        let value = self.varint()? as u32;
+        Ok((value >> 1) as i32 ^ -((value & 1) as i32))
+    }
+
+    fn f32(&mut self) -> io::Result<f32> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> io::Result<String> {
+        let len = self.varint()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| invalid("string is not UTF-8"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorded(events: &[(u64, u64, InputEvent)]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut recorder = InputRecorder::new(&mut out).unwrap();
+        for (frame, time_us, event) in events {
+            recorder.record(*frame, *time_us, event).unwrap();
+        }
+        out
+    }
+
+    #[test]
+    fn replays_inputs_at_recorded_frames() {
+        let data = recorded(&[
+            (
+                3,
+                50_000,
+                InputEvent::Key(KeyEvent::new(Keycode::ButtonA, KeyAction::Down, -7).with_text(0x1, 'A' as u32)),
+            ),
+            (
+                3,
+                50_000,
+                InputEvent::Touch(TouchEvent::new(
+                    TouchAction::PointerDown,
+                    1,
+                    vec![TouchPointer { id: 0, x: 1.5, y: 2.0 }, TouchPointer { id: 1, x: -3.25, y: 480.0 }],
+                )),
+            ),
+            (10, 166_666, InputEvent::Text(TextEvent::Commit("héllo".into()))),
+        ]);
+        let mut replay = InputReplay::parse(&data).unwrap();
+        let mut take_due = |frame| {
+            replay
+                .take_due(frame)
+                .into_iter()
+                .map(|record| record.event)
+                .collect::<Vec<_>>()
+        };
+        assert!(take_due(2).is_empty());
+        let due = take_due(3);
+        assert!(matches!(
+            due[..],
+            [
+                InputEvent::Key(_),
+                InputEvent::Touch(TouchEvent { action: TouchAction::PointerDown, pointer_id: 1, .. })
+            ]
+        ));
+        let InputEvent::Key(key) = &due[0] else { unreachable!() };
+        assert_eq!(*key, KeyEvent::new(Keycode::ButtonA, KeyAction::Down, -7).with_text(0x1, 65));
+        let InputEvent::Touch(touch) = &due[1] else { unreachable!() };
+        assert_eq!((touch.pointers[1].id, touch.pointers[1].x, touch.pointers[1].y), (1, -3.25, 480.0));
+        assert!(matches!(&take_due(20)[..], [InputEvent::Text(TextEvent::Commit(text))] if text == "héllo"));
+        assert!(replay.is_finished());
+    }
+
+    #[test]
+    fn rejects_foreign_and_truncated_files() {
+        assert!(InputReplay::parse(b"RIFF\x01").is_err());
+        assert!(InputReplay::parse(b"RFIR").is_err());
+        let mut data = recorded(&[]);
+        data.extend_from_slice(&[0, 0, 0xff]);
+        assert!(InputReplay::parse(&data).is_err());
+    }
+
+    #[test]
+    fn rejects_overflowing_counters() {
+        let mut data = recorded(&[(u64::MAX, 0, InputEvent::Text(TextEvent::DeleteSurrounding(1, 0)))]);
+        data.extend_from_slice(&[1, 0, KIND_TEXT_DELETE, 1, 0]);
+        let e = InputReplay::parse(&data).err().unwrap();
+        assert_eq!((e.kind(), e.to_string()), (ErrorKind::InvalidData, "frame counter overflow".to_string()));
+
+        let mut data = recorded(&[(0, u64::MAX, InputEvent::Text(TextEvent::DeleteSurrounding(1, 0)))]);
+        data.extend_from_slice(&[0, 1, KIND_TEXT_DELETE, 1, 0]);
+        assert!(InputReplay::parse(&data).is_err());
+    }
+
+    #[test]
+    fn keeps_whole_records_before_a_cut() {
+        let data = recorded(&[
+            (1, 16_666, InputEvent::Text(TextEvent::DeleteSurrounding(1, 0))),
+            (2, 33_333, InputEvent::Text(TextEvent::Commit("cut".into()))),
+        ]);
+        // Cut inside the last string, like a kill mid-write would.
+        let mut replay = InputReplay::parse(&data[..data.len() - 2]).unwrap();
+        assert!(matches!(
+            &replay.take_due(u64::MAX)[..],
+            [InputRecord { frame: 1, time_us: 16_666, event: InputEvent::Text(TextEvent::DeleteSurrounding(1, 0)) }]
+        ));
+        assert!(replay.is_finished());
+    }
+}
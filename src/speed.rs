@@ -0,0 +1,162 @@
+pub const MIN_SPEED: f64 = 0.25;
+pub const MAX_SPEED: f64 = 8.0;
+
+/// Host actions a remap profile can bind a key to, written as `@fast_forward`,
+/// `@pause` or `@frame_advance` in place of a target key.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Hotkey {
+    /// Switches between normal speed and the fast forward speed.
+    FastForward,
+    /// Pauses the movie, or resumes it at the speed it had.
+    Pause,
+    /// Pauses the movie if it is not already, then runs a single frame.
+    FrameAdvance,
+}
+
+impl TryFrom<&str> for Hotkey {
+    type Error = String;
+
+    fn try_from(name: &str) -> Result<Self, Self::Error> {
+        match name {
+            "fast_forward" => Ok(Hotkey::FastForward),
+            "pause" => Ok(Hotkey::Pause),
+            "frame_advance" => Ok(Hotkey::FrameAdvance),
+            _ => Err(format!("Unknown hotkey \"@{name}\"")),
+        }
+    }
+}
+
+/// How fast the movie runs relative to the wall clock. While stepping, the movie
+/// only advances by the frames asked for one at a time.
+pub struct SpeedControl {
+    speed: f64,
+    pub fast_forward_speed: f64,
+    fast_forwarding: bool,
+    stepping: bool,
+    pending_steps: u32,
+    /// The player volume from before muting, while away from 1x.
+    saved_volume: Option<f32>,
+}
+
+impl SpeedControl {
+    pub fn new(speed: f64, fast_forward_speed: f64) -> Self {
+        Self {
+            speed: speed.clamp(MIN_SPEED, MAX_SPEED),
+            fast_forward_speed: fast_forward_speed.clamp(MIN_SPEED, MAX_SPEED),
+            fast_forwarding: false,
+            stepping: false,
+            pending_steps: 0,
+            saved_volume: None,
+        }
+    }
+
+    /// Also ends fast forward and frame stepping.
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+        self.fast_forwarding = false;
+        self.stepping = false;
+        self.pending_steps = 0;
+    }
+
+    /// The multiplier for elapsed time, while not stepping.
+    pub fn speed(&self) -> f64 {
+        if self.fast_forwarding {
+            self.fast_forward_speed
+        } else {
+            self.speed
+        }
+    }
+
+    pub fn is_stepping(&self) -> bool {
+        self.stepping
+    }
+
+    pub fn step_frame(&mut self) {
+        self.stepping = true;
+        self.pending_steps += 1;
+    }
+
+    /// Frames to run now while stepping.
+    pub fn take_steps(&mut self) -> u32 {
+        std::mem::take(&mut self.pending_steps)
+    }
+
+    pub fn on_hotkey(&mut self, hotkey: Hotkey) {
+        match hotkey {
+            Hotkey::FastForward => {
+                self.fast_forwarding = !self.fast_forwarding;
+                self.stepping = false;
+            }
+            Hotkey::Pause => {
+                self.stepping = !self.stepping;
+                self.pending_steps = 0;
+            }
+            Hotkey::FrameAdvance => self.step_frame(),
+        }
+    }
+
+    fn is_realtime(&self) -> bool {
+        !self.stepping && self.speed() == 1.0
+    }
+
+    /// Sounds pile up when sped up and drag when slowed down, so the movie is muted
+    /// away from 1x. Returns the volume to give the player, if it has to change.
+    pub fn volume_change(&mut self, volume: f32) -> Option<f32> {
+        match (self.is_realtime(), self.saved_volume) {
+            (false, None) => {
+                self.saved_volume = Some(volume);
+                Some(0.0)
+            }
+            (true, Some(saved)) => {
+                self.saved_volume = None;
+                Some(saved)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamps_and_toggles_fast_forward() {
+        let mut speed = SpeedControl::new(20.0, 4.0);
+        assert_eq!(speed.speed(), MAX_SPEED);
+        speed.set_speed(0.1);
+        assert_eq!(speed.speed(), MIN_SPEED);
+        speed.set_speed(1.0);
+        speed.on_hotkey(Hotkey::FastForward);
+        assert_eq!(speed.speed(), 4.0);
+        speed.on_hotkey(Hotkey::FastForward);
+        assert_eq!(speed.speed(), 1.0);
+    }
+
+    #[test]
+    fn frame_advance_runs_one_frame_per_press() {
+        let mut speed = SpeedControl::new(1.0, 4.0);
+        speed.on_hotkey(Hotkey::FrameAdvance);
+        speed.on_hotkey(Hotkey::FrameAdvance);
+        assert!(speed.is_stepping());
+        assert_eq!(speed.take_steps(), 2);
+        assert_eq!(speed.take_steps(), 0);
+        speed.on_hotkey(Hotkey::Pause);
+        assert!(!speed.is_stepping());
+        speed.on_hotkey(Hotkey::Pause);
+        assert!(speed.is_stepping());
+        assert_eq!(speed.take_steps(), 0);
+    }
+
+    #[test]
+    fn mutes_away_from_normal_speed() {
+        let mut speed = SpeedControl::new(1.0, 4.0);
+        assert_eq!(speed.volume_change(0.8), None);
+        speed.on_hotkey(Hotkey::FastForward);
+        assert_eq!(speed.volume_change(0.8), Some(0.0));
+        speed.set_speed(0.5);
+        assert_eq!(speed.volume_change(0.0), None);
+        speed.set_speed(1.0);
+        assert_eq!(speed.volume_change(0.0), Some(0.8));
+    }
+}
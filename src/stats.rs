@@ -0,0 +1,165 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// Samples the rolling averages are taken over, about two seconds at 60 fps.
+const WINDOW: usize = 120;
+const PUBLISH_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Default)]
+struct RollingStat {
+    samples: VecDeque<f64>,
+    sum: f64,
+}
+
+impl RollingStat {
+    fn push(&mut self, value: f64) {
+        if self.samples.len() == WINDOW {
+            self.sum -= self.samples.pop_front().unwrap_or_default();
+        }
+        self.samples.push_back(value);
+        self.sum += value;
+    }
+
+    fn average(&self) -> f64 {
+        if self.samples.is_empty() {
+            0.0
+        } else {
+            self.sum / self.samples.len() as f64
+        }
+    }
+
+    fn max(&self) -> f64 {
+        self.samples.iter().copied().fold(0.0, f64::max)
+    }
+}
+
+/// What the host gets from `nativeGetStats`, refreshed twice a second.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StatsSnapshot {
+    pub tick_ms: f64,
+    pub tick_ms_max: f64,
+    pub render_ms: f64,
+    pub render_ms_max: f64,
+    /// Frames actually presented per second.
+    pub fps: f64,
+    pub frames_rendered: u64,
+    pub frames_skipped: u64,
+    pub audio_underruns: u64,
+}
+
+impl StatsSnapshot {
+    pub const fn new() -> Self {
+        Self {
+            tick_ms: 0.0,
+            tick_ms_max: 0.0,
+            render_ms: 0.0,
+            render_ms_max: 0.0,
+            fps: 0.0,
+            frames_rendered: 0,
+            frames_skipped: 0,
+            audio_underruns: 0,
+        }
+    }
+
+    pub fn overlay_text(&self) -> String {
+        format!(
+            "{:.1} fps\ntick {:.2} ms (max {:.2})\nrender {:.2} ms (max {:.2})\nskipped {} / {}\nunderruns {}",
+            self.fps,
+            self.tick_ms,
+            self.tick_ms_max,
+            self.render_ms,
+            self.render_ms_max,
+            self.frames_skipped,
+            self.frames_rendered + self.frames_skipped,
+            self.audio_underruns,
+        )
+    }
+}
+
+/// Rolling timings of the worker loop.
+#[derive(Default)]
+pub struct PerfStats {
+    tick_ms: RollingStat,
+    render_ms: RollingStat,
+    /// Time between presented frames.
+    frame_interval_ms: RollingStat,
+    last_render: Option<Instant>,
+    published_at: Option<Instant>,
+}
+
+impl PerfStats {
+    pub fn on_tick(&mut self, tick_time: Duration) {
+        self.tick_ms.push(tick_time.as_secs_f64() * 1000.0);
+    }
+
+    pub fn on_render(&mut self, render_time: Duration, now: Instant) {
+        self.render_ms.push(render_time.as_secs_f64() * 1000.0);
+        if let Some(last) = self.last_render.replace(now) {
+            self.frame_interval_ms
+                .push(now.duration_since(last).as_secs_f64() * 1000.0);
+        }
+    }
+
+    /// Forgets the gap a suspension leaves between two frames.
+    pub fn on_resume(&mut self) {
+        self.last_render = None;
+    }
+
+    pub fn publish_due(&mut self, now: Instant) -> bool {
+        if self
+            .published_at
+            .is_some_and(|at| now.duration_since(at) < PUBLISH_INTERVAL)
+        {
+            return false;
+        }
+        self.published_at = Some(now);
+        true
+    }
+
+    pub fn snapshot(&self, frames_rendered: u64, frames_skipped: u64, audio_underruns: u64) -> StatsSnapshot {
+        let interval = self.frame_interval_ms.average();
+        StatsSnapshot {
+            tick_ms: self.tick_ms.average(),
+            tick_ms_max: self.tick_ms.max(),
+            render_ms: self.render_ms.average(),
+            render_ms_max: self.render_ms.max(),
+            fps: if interval > 0.0 { 1000.0 / interval } else { 0.0 },
+            frames_rendered,
+            frames_skipped,
+            audio_underruns,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn averages_over_a_rolling_window() {
+        let mut stat = RollingStat::default();
+        for value in 0..WINDOW + 10 {
+            stat.push(if value < 10 { 100.0 } else { 2.0 });
+        }
+        assert_eq!(stat.average(), 2.0);
+        assert_eq!(stat.max(), 2.0);
+    }
+
+    #[test]
+    fn measures_presented_frame_rate() {
+        let start = Instant::now();
+        let mut stats = PerfStats::default();
+        for frame in 0..5 {
+            stats.on_tick(Duration::from_millis(3));
+            stats.on_render(Duration::from_millis(5), start + Duration::from_millis(frame * 20));
+        }
+        let snapshot = stats.snapshot(5, 1, 0);
+        assert!((snapshot.fps - 50.0).abs() < 1e-9);
+        assert!((snapshot.render_ms - 5.0).abs() < 1e-9);
+        assert!(stats.publish_due(start));
+        assert!(!stats.publish_due(start + Duration::from_millis(100)));
+        assert!(stats.publish_due(start + PUBLISH_INTERVAL));
+    }
+}
@@ -0,0 +1,201 @@
+use std::f32::consts::PI;
+
+use ndk::event::Keycode;
+
+use crate::input::KeyAction;
+
+pub const AXIS_X: i32 = 0;
+pub const AXIS_Y: i32 = 1;
+pub const AXIS_Z: i32 = 11;
+pub const AXIS_RZ: i32 = 14;
+pub const AXIS_HAT_X: i32 = 15;
+pub const AXIS_HAT_Y: i32 = 16;
+
+/// Directions in the order `up, down, left, right`.
+const DIRECTION_KEYS: [Keycode; 4] = [
+    Keycode::DpadUp,
+    Keycode::DpadDown,
+    Keycode::DpadLeft,
+    Keycode::DpadRight,
+];
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum StickMode {
+    Off,
+    /// Deflection presses the arrow keys.
+    Keys,
+    /// Deflection moves the virtual mouse cursor.
+    Mouse,
+}
+
+impl From<&str> for StickMode {
+    fn from(mode: &str) -> Self {
+        match mode {
+            "keys" => StickMode::Keys,
+            "mouse" => StickMode::Mouse,
+            _ => StickMode::Off,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct StickConfig {
+    pub mode: StickMode,
+    pub deadzone: f32,
+    /// Diagonals press two keys at once, otherwise only the dominant direction counts.
+    pub eight_way: bool,
+    /// Cursor speed at full deflection, in viewport pixels per second.
+    pub mouse_speed: f64,
+    /// Response curve exponent, above 1 gives finer control near the center.
+    pub mouse_acceleration: f64,
+}
+
+impl Default for StickConfig {
+    fn default() -> Self {
+        Self {
+            mode: StickMode::Off,
+            deadzone: 0.25,
+            eight_way: true,
+            mouse_speed: 600.0,
+            mouse_acceleration: 2.0,
+        }
+    }
+}
+
+/// Axis values of a joystick `MotionEvent`, each in `-1.0..=1.0`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JoystickEvent {
+    pub device_id: i32,
+    pub left: (f32, f32),
+    pub right: (f32, f32),
+    pub hat: (f32, f32),
+}
+
+pub struct AnalogStick {
+    pub config: StickConfig,
+    position: (f32, f32),
+    pressed: [bool; 4],
+}
+
+impl AnalogStick {
+    pub fn new(config: StickConfig) -> Self {
+        Self {
+            config,
+            position: (0.0, 0.0),
+            pressed: [false; 4],
+        }
+    }
+
+    /// Stores the new deflection and returns the arrow key transitions it causes.
+    pub fn update(&mut self, x: f32, y: f32) -> Vec<(Keycode, KeyAction)> {
+        self.position = (x, y);
+        let pressed = if self.config.mode == StickMode::Keys {
+            directions(x, y, self.config.deadzone, self.config.eight_way)
+        } else {
+            [false; 4]
+        };
+        let mut transitions = Vec::new();
+        for (index, keycode) in DIRECTION_KEYS.iter().enumerate() {
+            if pressed[index] != self.pressed[index] {
+                transitions.push((*keycode, KeyAction::from(pressed[index])));
+            }
+        }
+        self.pressed = pressed;
+        transitions
+    }
+
+    /// Cursor velocity in pixels per second, zero unless in mouse mode.
+    pub fn cursor_velocity(&self) -> (f64, f64) {
+        if self.config.mode != StickMode::Mouse {
+            return (0.0, 0.0);
+        }
+        let (x, y) = self.position;
+        let magnitude = x.hypot(y);
+        if magnitude <= self.config.deadzone {
+            return (0.0, 0.0);
+        }
+        let scaled = ((magnitude.min(1.0) - self.config.deadzone) / (1.0 - self.config.deadzone)) as f64;
+        let speed = self.config.mouse_speed * scaled.powf(self.config.mouse_acceleration);
+        (
+            speed * (x / magnitude) as f64,
+            speed * (y / magnitude) as f64,
+        )
+    }
+}
+
+/// Which of `up, down, left, right` a deflection selects. Stick y grows downwards.
+fn directions(x: f32, y: f32, deadzone: f32, eight_way: bool) -> [bool; 4] {
+    if x.hypot(y) <= deadzone {
+        return [false; 4];
+    }
+    let sectors = if eight_way { 8.0 } else { 4.0 };
+    let sector = ((y.atan2(x) / (2.0 * PI) * sectors).round() as i32).rem_euclid(sectors as i32);
+    // Sectors count clockwise from the right, in screen coordinates.
+    let (horizontal, vertical) = if eight_way {
+        [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)][sector as usize]
+    } else {
+        [(1, 0), (0, 1), (-1, 0), (0, -1)][sector as usize]
+    };
+    [vertical < 0, vertical > 0, horizontal < 0, horizontal > 0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys_stick(eight_way: bool) -> AnalogStick {
+        AnalogStick::new(StickConfig {
+            mode: StickMode::Keys,
+            eight_way,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn deadzone_swallows_small_deflection() {
+        assert_eq!(directions(0.1, -0.2, 0.25, true), [false; 4]);
+    }
+
+    #[test]
+    fn four_way_picks_dominant_axis() {
+        assert_eq!(directions(0.6, -0.5, 0.25, false), [false, false, false, true]);
+        assert_eq!(directions(0.5, -0.6, 0.25, false), [true, false, false, false]);
+        assert_eq!(directions(-0.9, 0.0, 0.25, false), [false, false, true, false]);
+    }
+
+    #[test]
+    fn eight_way_presses_diagonals() {
+        assert_eq!(directions(0.7, -0.7, 0.25, true), [true, false, false, true]);
+        assert_eq!(directions(-0.7, 0.7, 0.25, true), [false, true, true, false]);
+        assert_eq!(directions(0.0, 1.0, 0.25, true), [false, true, false, false]);
+    }
+
+    #[test]
+    fn update_reports_transitions_only() {
+        let mut stick = keys_stick(true);
+        assert_eq!(stick.update(1.0, 0.0), vec![(Keycode::DpadRight, KeyAction::Down)]);
+        assert!(stick.update(0.9, 0.1).is_empty());
+        assert_eq!(
+            stick.update(0.7, 0.7),
+            vec![(Keycode::DpadDown, KeyAction::Down)]
+        );
+        assert_eq!(
+            stick.update(0.0, 0.0),
+            vec![(Keycode::DpadDown, KeyAction::Up), (Keycode::DpadRight, KeyAction::Up)]
+        );
+    }
+
+    #[test]
+    fn mouse_velocity_follows_curve() {
+        let stick = AnalogStick {
+            config: StickConfig {
+                mode: StickMode::Mouse,
+                deadzone: 0.0,
+                ..Default::default()
+            },
+            position: (0.5, 0.0),
+            pressed: [false; 4],
+        };
+        assert_eq!(stick.cursor_velocity(), (150.0, 0.0));
+    }
+}
@@ -0,0 +1,454 @@
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant},
+};
+
+use ruffle_core::{PlayerEvent, events::MouseButton};
+
+use crate::input::{TouchAction, TouchEvent, TouchPointer};
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum TouchMode {
+    /// The finger is the mouse, pressed while it is down.
+    Direct,
+    /// The screen is a trackpad moving an on-stage cursor.
+    Trackpad,
+    /// Absolute positions, but a touch only clicks once it is known to be a tap.
+    TapToClick,
+}
+
+impl From<&str> for TouchMode {
+    fn from(mode: &str) -> Self {
+        match mode {
+            "trackpad" => TouchMode::Trackpad,
+            "tap" => TouchMode::TapToClick,
+            _ => TouchMode::Direct,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TouchConfig {
+    pub mode: TouchMode,
+    /// Distance in viewport pixels a finger may travel and still count as a tap.
+    pub drag_threshold: f64,
+    pub long_press: Duration,
+    pub double_tap: Duration,
+    pub trackpad_sensitivity: f64,
+}
+
+impl Default for TouchConfig {
+    fn default() -> Self {
+        Self {
+            mode: TouchMode::Direct,
+            drag_threshold: 12.0,
+            long_press: Duration::from_millis(500),
+            double_tap: Duration::from_millis(300),
+            trackpad_sensitivity: 1.5,
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+enum GestureState {
+    /// Down, not yet decided between tap, drag and long press.
+    Pending,
+    /// Trackpad only: moving the cursor without a button.
+    Tracking,
+    /// Left button held.
+    Dragging,
+    /// Nothing more to send until every finger is lifted.
+    Done,
+}
+
+struct Gesture {
+    pointer_id: i32,
+    origin: (f64, f64),
+    last: (f64, f64),
+    started_at: Instant,
+    state: GestureState,
+}
+
+/// Turns touch gestures into Flash mouse events for the configured mode.
+/// Only the first finger down drives the mouse, and nobody takes over
+/// until every finger is lifted.
+pub struct TouchTranslator {
+    pub config: TouchConfig,
+    gesture: Option<Gesture>,
+    active_pointers: HashSet<i32>,
+    last_tap: Option<(Instant, (f64, f64))>,
+    bounds: (f64, f64),
+    cursor: Option<(f64, f64)>,
+    cursor_dirty: bool,
+}
+
+impl TouchTranslator {
+    pub fn new(config: TouchConfig) -> Self {
+        Self {
+            config,
+            gesture: None,
+            active_pointers: HashSet::new(),
+            last_tap: None,
+            bounds: (0.0, 0.0),
+            cursor: None,
+            cursor_dirty: false,
+        }
+    }
+
+    pub fn on_touch(&mut self, event: &TouchEvent, now: Instant, bounds: (f64, f64)) -> Vec<PlayerEvent> {
+        self.bounds = bounds;
+        let mut out = Vec::new();
+        let primary = self.gesture.as_ref().map(|gesture| gesture.pointer_id);
+        match event.action {
+            TouchAction::Down | TouchAction::PointerDown => {
+                self.active_pointers.insert(event.pointer_id);
+                if primary.is_none() {
+                    if let Some(pointer) = find_pointer(event, event.pointer_id) {
+                        self.begin(pointer, now, &mut out);
+                    }
+                }
+            }
+            TouchAction::Move => {
+                if let Some(pointer) = primary.and_then(|id| find_pointer(event, id)) {
+                    self.moved(pointer, &mut out);
+                }
+            }
+            TouchAction::Up | TouchAction::PointerUp => {
+                self.active_pointers.remove(&event.pointer_id);
+                if primary == Some(event.pointer_id) {
+                    if let Some(pointer) = find_pointer(event, event.pointer_id) {
+                        self.moved(pointer, &mut out);
+                    }
+                    self.end(now, &mut out);
+                }
+                if self.active_pointers.is_empty() || event.action == TouchAction::Up {
+                    self.active_pointers.clear();
+                    self.gesture = None;
+                }
+            }
+            TouchAction::Cancel => {
+                if let Some(gesture) = &self.gesture {
+                    if gesture.state == GestureState::Dragging {
+                        let (x, y) = self.mouse_position(gesture.last);
+                        out.push(PlayerEvent::MouseUp { x, y, button: MouseButton::Left });
+                    }
+                }
+                self.active_pointers.clear();
+                self.gesture = None;
+            }
+            TouchAction::Other => (),
+        }
+        out
+    }
+
+    /// Fires the long press once a finger has stayed still long enough.
+    pub fn poll(&mut self, now: Instant) -> Vec<PlayerEvent> {
+        let mut out = Vec::new();
+        if self.config.mode == TouchMode::Direct {
+            return out;
+        }
+        let Some(gesture) = &self.gesture else {
+            return out;
+        };
+        if gesture.state == GestureState::Pending
+            && now.duration_since(gesture.started_at) >= self.config.long_press
+        {
+            let position = self.mouse_position(gesture.origin);
+            click(&mut out, position, MouseButton::Right);
+            self.last_tap = None;
+            if let Some(gesture) = &mut self.gesture {
+                gesture.state = GestureState::Done;
+            }
+        }
+        out
+    }
+
+    /// The virtual cursor, if it moved since the last call.
+    pub fn take_cursor_update(&mut self) -> Option<(f64, f64)> {
+        if !self.cursor_dirty {
+            return None;
+        }
+        self.cursor_dirty = false;
+        self.cursor
+    }
+
+    /// Moves the virtual cursor shared with other relative devices, such as analog sticks.
+    pub fn move_cursor(&mut self, dx: f64, dy: f64, bounds: (f64, f64)) -> (f64, f64) {
+        self.bounds = bounds;
+        let (x, y) = self.cursor_or_center();
+        let cursor = (
+            (x + dx).clamp(0.0, self.bounds.0),
+            (y + dy).clamp(0.0, self.bounds.1),
+        );
+        self.cursor = Some(cursor);
+        self.cursor_dirty = true;
+        cursor
+    }
+
+    pub fn cursor_position(&self) -> (f64, f64) {
+        self.cursor_or_center()
+    }
+
+    fn begin(&mut self, pointer: &TouchPointer, now: Instant, out: &mut Vec<PlayerEvent>) {
+        let position = (pointer.x, pointer.y);
+        let state = match self.config.mode {
+            TouchMode::Direct => {
+                out.push(PlayerEvent::MouseDown {
+                    x: pointer.x,
+                    y: pointer.y,
+                    button: MouseButton::Left,
+                    index: None,
+                });
+                GestureState::Dragging
+            }
+            TouchMode::TapToClick => GestureState::Pending,
+            TouchMode::Trackpad => {
+                // Tap, then touch again: drag with the button held.
+                if self.is_double_tap(now, None) {
+                    let (x, y) = self.cursor_or_center();
+                    out.push(PlayerEvent::MouseDown { x, y, button: MouseButton::Left, index: None });
+                    GestureState::Dragging
+                } else {
+                    GestureState::Pending
+                }
+            }
+        };
+        self.gesture = Some(Gesture {
+            pointer_id: pointer.id,
+            origin: position,
+            last: position,
+            started_at: now,
+            state,
+        });
+    }
+
+    fn moved(&mut self, pointer: &TouchPointer, out: &mut Vec<PlayerEvent>) {
+        let mode = self.config.mode;
+        let drag_threshold = self.config.drag_threshold;
+        let sensitivity = self.config.trackpad_sensitivity;
+        let Some(gesture) = &mut self.gesture else {
+            return;
+        };
+        let position = (pointer.x, pointer.y);
+        if position == gesture.last {
+            return;
+        }
+        if gesture.state == GestureState::Pending {
+            if distance(gesture.origin, position) <= drag_threshold {
+                return;
+            }
+            gesture.state = match mode {
+                TouchMode::Trackpad => GestureState::Tracking,
+                _ => {
+                    let (x, y) = gesture.origin;
+                    out.push(PlayerEvent::MouseMove { x, y });
+                    out.push(PlayerEvent::MouseDown { x, y, button: MouseButton::Left, index: None });
+                    GestureState::Dragging
+                }
+            };
+        }
+        let delta = (position.0 - gesture.last.0, position.1 - gesture.last.1);
+        gesture.last = position;
+        let state = gesture.state;
+        match (mode, state) {
+            (TouchMode::Trackpad, GestureState::Tracking | GestureState::Dragging) => {
+                let (x, y) = self.move_cursor(delta.0 * sensitivity, delta.1 * sensitivity, self.bounds);
+                out.push(PlayerEvent::MouseMove { x, y });
+            }
+            (_, GestureState::Dragging) => {
+                out.push(PlayerEvent::MouseMove { x: position.0, y: position.1 });
+            }
+            _ => (),
+        }
+    }
+
+    fn end(&mut self, now: Instant, out: &mut Vec<PlayerEvent>) {
+        let Some(gesture) = &self.gesture else {
+            return;
+        };
+        let (state, origin, last) = (gesture.state, gesture.origin, gesture.last);
+        match state {
+            GestureState::Pending => {
+                // Snap the second tap of a double tap onto the first one,
+                // fingers rarely land on the same pixel twice.
+                let position = match self.last_tap {
+                    Some((_, previous)) if self.is_double_tap(now, Some(origin)) => previous,
+                    _ => self.mouse_position(origin),
+                };
+                click(out, position, MouseButton::Left);
+                self.last_tap = Some((now, position));
+            }
+            GestureState::Dragging => {
+                let (x, y) = self.mouse_position(last);
+                out.push(PlayerEvent::MouseUp { x, y, button: MouseButton::Left });
+                self.last_tap = None;
+            }
+            GestureState::Tracking | GestureState::Done => (),
+        }
+        if let Some(gesture) = &mut self.gesture {
+            gesture.state = GestureState::Done;
+        }
+    }
+
+    fn is_double_tap(&self, now: Instant, position: Option<(f64, f64)>) -> bool {
+        let Some((at, previous)) = self.last_tap else {
+            return false;
+        };
+        now.duration_since(at) <= self.config.double_tap
+            && position.is_none_or(|position| {
+                distance(previous, position) <= self.config.drag_threshold * 2.0
+            })
+    }
+
+    fn mouse_position(&self, finger: (f64, f64)) -> (f64, f64) {
+        match self.config.mode {
+            TouchMode::Trackpad => self.cursor_or_center(),
+            _ => finger,
+        }
+    }
+
+    fn cursor_or_center(&self) -> (f64, f64) {
+        self.cursor
+            .unwrap_or((self.bounds.0 / 2.0, self.bounds.1 / 2.0))
+    }
+}
+
+fn find_pointer(event: &TouchEvent, id: i32) -> Option<&TouchPointer> {
+    event.pointers.iter().find(|pointer| pointer.id == id)
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+fn click(out: &mut Vec<PlayerEvent>, (x, y): (f64, f64), button: MouseButton) {
+    out.push(PlayerEvent::MouseMove { x, y });
+    out.push(PlayerEvent::MouseDown { x, y, button, index: None });
+    out.push(PlayerEvent::MouseUp { x, y, button });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDS: (f64, f64) = (800.0, 600.0);
+
+    fn touch(action: TouchAction, id: i32, x: f64, y: f64) -> TouchEvent {
+        TouchEvent::new(action, id, vec![TouchPointer { id, x, y }])
+    }
+
+    fn translator(mode: TouchMode) -> TouchTranslator {
+        TouchTranslator::new(TouchConfig {
+            mode,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn direct_presses_on_down() {
+        let mut it = translator(TouchMode::Direct);
+        let now = Instant::now();
+        let out = it.on_touch(&touch(TouchAction::Down, 0, 10.0, 20.0), now, BOUNDS);
+        assert!(matches!(out[..], [PlayerEvent::MouseDown { x: 10.0, y: 20.0, button: MouseButton::Left, .. }]));
+        let out = it.on_touch(&touch(TouchAction::Move, 0, 15.0, 20.0), now, BOUNDS);
+        assert!(matches!(out[..], [PlayerEvent::MouseMove { x: 15.0, y: 20.0 }]));
+        let out = it.on_touch(&touch(TouchAction::Up, 0, 15.0, 20.0), now, BOUNDS);
+        assert!(matches!(out[..], [PlayerEvent::MouseUp { x: 15.0, .. }]));
+    }
+
+    #[test]
+    fn second_finger_does_not_take_over() {
+        let mut it = translator(TouchMode::Direct);
+        let now = Instant::now();
+        it.on_touch(&touch(TouchAction::Down, 0, 10.0, 10.0), now, BOUNDS);
+        let out = it.on_touch(&touch(TouchAction::PointerDown, 1, 50.0, 50.0), now, BOUNDS);
+        assert!(out.is_empty());
+        let out = it.on_touch(&touch(TouchAction::PointerUp, 0, 10.0, 10.0), now, BOUNDS);
+        assert!(matches!(out[..], [PlayerEvent::MouseUp { .. }]));
+        let out = it.on_touch(&touch(TouchAction::Move, 1, 60.0, 60.0), now, BOUNDS);
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn tap_clicks_only_on_release() {
+        let mut it = translator(TouchMode::TapToClick);
+        let now = Instant::now();
+        assert!(it.on_touch(&touch(TouchAction::Down, 0, 10.0, 10.0), now, BOUNDS).is_empty());
+        assert!(it.on_touch(&touch(TouchAction::Move, 0, 14.0, 10.0), now, BOUNDS).is_empty());
+        let out = it.on_touch(&touch(TouchAction::Up, 0, 14.0, 10.0), now, BOUNDS);
+        assert!(matches!(
+            out[..],
+            [
+                PlayerEvent::MouseMove { x: 10.0, y: 10.0 },
+                PlayerEvent::MouseDown { button: MouseButton::Left, .. },
+                PlayerEvent::MouseUp { button: MouseButton::Left, .. },
+            ]
+        ));
+    }
+
+    #[test]
+    fn tap_drags_past_threshold() {
+        let mut it = translator(TouchMode::TapToClick);
+        let now = Instant::now();
+        it.on_touch(&touch(TouchAction::Down, 0, 10.0, 10.0), now, BOUNDS);
+        let out = it.on_touch(&touch(TouchAction::Move, 0, 40.0, 10.0), now, BOUNDS);
+        assert!(matches!(
+            out[..],
+            [
+                PlayerEvent::MouseMove { x: 10.0, .. },
+                PlayerEvent::MouseDown { x: 10.0, .. },
+                PlayerEvent::MouseMove { x: 40.0, .. },
+            ]
+        ));
+        let out = it.on_touch(&touch(TouchAction::Up, 0, 40.0, 10.0), now, BOUNDS);
+        assert!(matches!(out[..], [PlayerEvent::MouseUp { x: 40.0, .. }]));
+    }
+
+    #[test]
+    fn long_press_right_clicks() {
+        let mut it = translator(TouchMode::TapToClick);
+        let now = Instant::now();
+        it.on_touch(&touch(TouchAction::Down, 0, 10.0, 10.0), now, BOUNDS);
+        assert!(it.poll(now + Duration::from_millis(100)).is_empty());
+        let out = it.poll(now + Duration::from_millis(600));
+        assert!(matches!(
+            out[..],
+            [
+                PlayerEvent::MouseMove { .. },
+                PlayerEvent::MouseDown { button: MouseButton::Right, .. },
+                PlayerEvent::MouseUp { button: MouseButton::Right, .. },
+            ]
+        ));
+        assert!(it.on_touch(&touch(TouchAction::Up, 0, 10.0, 10.0), now, BOUNDS).is_empty());
+    }
+
+    #[test]
+    fn double_tap_lands_on_first_tap() {
+        let mut it = translator(TouchMode::TapToClick);
+        let now = Instant::now();
+        it.on_touch(&touch(TouchAction::Down, 0, 10.0, 10.0), now, BOUNDS);
+        it.on_touch(&touch(TouchAction::Up, 0, 10.0, 10.0), now, BOUNDS);
+        let later = now + Duration::from_millis(150);
+        it.on_touch(&touch(TouchAction::Down, 0, 18.0, 12.0), later, BOUNDS);
+        let out = it.on_touch(&touch(TouchAction::Up, 0, 18.0, 12.0), later, BOUNDS);
+        assert!(matches!(out[..], [PlayerEvent::MouseMove { x: 10.0, y: 10.0 }, ..]));
+    }
+
+    #[test]
+    fn trackpad_moves_cursor_relative() {
+        let mut it = translator(TouchMode::Trackpad);
+        let now = Instant::now();
+        it.on_touch(&touch(TouchAction::Down, 0, 100.0, 100.0), now, BOUNDS);
+        let out = it.on_touch(&touch(TouchAction::Move, 0, 120.0, 100.0), now, BOUNDS);
+        assert!(matches!(out[..], [PlayerEvent::MouseMove { x: 430.0, y: 300.0 }]));
+        assert_eq!(it.take_cursor_update(), Some((430.0, 300.0)));
+        assert_eq!(it.take_cursor_update(), None);
+        let out = it.on_touch(&touch(TouchAction::Up, 0, 120.0, 100.0), now, BOUNDS);
+        assert!(out.is_empty());
+
+        let later = now + Duration::from_secs(1);
+        it.on_touch(&touch(TouchAction::Down, 0, 500.0, 500.0), later, BOUNDS);
+        let out = it.on_touch(&touch(TouchAction::Up, 0, 500.0, 500.0), later, BOUNDS);
+        assert!(matches!(out[..], [PlayerEvent::MouseMove { x: 430.0, y: 300.0 }, ..]));
+    }
+}
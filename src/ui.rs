@@ -0,0 +1,260 @@
+use std::sync::{Arc, Mutex};
+
+use jni::{JNIEnv, JavaVM, errors::Result as JniResult, objects::{GlobalRef, JObject, JValue}};
+use log::error;
+use ruffle_core::{
+    FontQuery,
+    backend::ui::{
+        DialogResultFuture, FileDialogResult, FileFilter, FontDefinition, FullscreenError, LanguageIdentifier,
+        MouseCursor, NullUiBackend, UiBackend,
+    },
+};
+use tokio::sync::oneshot;
+use url::Url;
+
+use crate::{
+    filedialog::{self, AndroidFileDialogResult, DialogAnswer, filter_extensions},
+    ime,
+};
+
+/// The host clipboard as last reported, so text fields can paste without a JNI round trip.
+static CLIPBOARD: Mutex<String> = Mutex::new(String::new());
+
+/// Called when the host clipboard changes.
+pub fn mirror_clipboard(text: String) {
+    *CLIPBOARD.lock().unwrap() = text;
+}
+
+/// The Java `Ruffle` instance, callable from any thread.
+#[derive(Clone)]
+pub struct JavaHost {
+    vm: Arc<JavaVM>,
+    thiz: GlobalRef,
+}
+
+impl JavaHost {
+    pub fn new(vm: JavaVM, thiz: GlobalRef) -> Self {
+        Self {
+            vm: Arc::new(vm),
+            thiz,
+        }
+    }
+
+    pub fn thiz(&self) -> &GlobalRef {
+        &self.thiz
+    }
+
+    /// Runs `f` in a local frame, since threads attached for good never free local refs otherwise.
+    /// A Java exception thrown by the host is logged and cleared, so later calls still work.
+    pub fn with_env<T>(&self, f: impl FnOnce(&mut JNIEnv) -> JniResult<T>) -> JniResult<T> {
+        let mut env = self.vm.attach_current_thread()?;
+        let result = env.with_local_frame(8, |env| f(env));
+        if result.is_err() && env.exception_check().unwrap_or(false) {
+            let _ = env.exception_describe();
+            let _ = env.exception_clear();
+        }
+        result
+    }
+
+    pub fn call(&self, name: &str, sig: &str, args: &[JValue]) {
+        let result = self.with_env(|env| env.call_method(&self.thiz, name, sig, args).map(|_| ()));
+        if let Err(e) = result {
+            error!("Failed to call host method {name}! {e}");
+        }
+    }
+
+    /// Calls a host method taking a single string.
+    pub fn call_with_str(&self, name: &str, value: &str) {
+        self.call_with_strs(name, &[value]);
+    }
+
+    /// Calls a host method taking only strings.
+    pub fn call_with_strs(&self, name: &str, values: &[&str]) {
+        let sig = format!("({})V", "Ljava/lang/String;".repeat(values.len()));
+        let result = self.with_env(|env| {
+            let values = values
+                .iter()
+                .map(|value| env.new_string(value))
+                .collect::<JniResult<Vec<_>>>()?;
+            let args = values.iter().map(|value| JValue::Object(value)).collect::<Vec<_>>();
+            env.call_method(&self.thiz, name, &sig, &args).map(|_| ())
+        });
+        if let Err(e) = result {
+            error!("Failed to call host method {name}! {e}");
+        }
+    }
+}
+
+/// Resolves once the host answers, a dropped request counts as cancelled.
+fn dialog_result(receiver: oneshot::Receiver<DialogAnswer>) -> DialogResultFuture {
+    Box::pin(async move {
+        let answer = receiver.await.unwrap_or(DialogAnswer::Cancelled);
+        Ok(Box::new(AndroidFileDialogResult::from(answer)) as Box<dyn FileDialogResult>)
+    })
+}
+
+/// What `onSetCursor` receives, -1 while the movie hides the mouse.
+fn cursor_id(cursor: MouseCursor) -> i32 {
+    match cursor {
+        MouseCursor::Arrow => 0,
+        MouseCursor::Hand => 1,
+        MouseCursor::IBeam => 2,
+        MouseCursor::Grab => 3,
+    }
+}
+
+/// Forwards what the host can act on, everything else behaves like the null backend.
+/// Called on the worker thread, so host methods must hop to the UI thread themselves.
+pub struct AndroidUiBackend {
+    inner: NullUiBackend,
+    host: JavaHost,
+    cursor: MouseCursor,
+    /// The null backend always reports a visible mouse, so track it here.
+    visible: bool,
+}
+
+impl AndroidUiBackend {
+    pub fn new(host: JavaHost) -> Self {
+        Self {
+            inner: NullUiBackend::new(),
+            host,
+            cursor: MouseCursor::Arrow,
+            visible: true,
+        }
+    }
+}
+
+impl UiBackend for AndroidUiBackend {
+    fn mouse_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn set_mouse_visible(&mut self, visible: bool) {
+        if visible != self.visible {
+            let id = if visible { cursor_id(self.cursor) } else { -1 };
+            self.host.call("onSetCursor", "(I)V", &[JValue::from(id)]);
+        }
+        self.visible = visible;
+        self.inner.set_mouse_visible(visible)
+    }
+
+    fn set_mouse_cursor(&mut self, cursor: MouseCursor) {
+        if cursor != self.cursor && self.visible {
+            self.host.call("onSetCursor", "(I)V", &[JValue::from(cursor_id(cursor))]);
+        }
+        self.cursor = cursor;
+        self.inner.set_mouse_cursor(cursor)
+    }
+
+    fn clipboard_content(&mut self) -> String {
+        CLIPBOARD.lock().unwrap().clone()
+    }
+
+    /// The mirror is updated right away, the host may take a while to report the change back.
+    fn set_clipboard_content(&mut self, content: String) {
+        self.host.call_with_str("onSetClipboard", &content);
+        mirror_clipboard(content);
+    }
+
+    /// The host switches its window, the stage follows once the surface is resized.
+    fn set_fullscreen(&mut self, is_full: bool) -> Result<(), FullscreenError> {
+        self.host.call("onFullscreenRequested", "(Z)V", &[JValue::from(is_full)]);
+        Ok(())
+    }
+
+    fn display_root_movie_download_failed_message(&self, invalid_swf: bool, fetched_error: String) {
+        let message = if invalid_swf {
+            format!("The movie is not a valid SWF file. {fetched_error}")
+        } else {
+            format!("The movie could not be loaded. {fetched_error}")
+        };
+        self.host.call_with_str("onShowMessage", &message);
+    }
+
+    fn message(&self, message: &str) {
+        self.host.call_with_str("onShowMessage", message);
+    }
+
+    fn display_unsupported_video(&self, url: Url) {
+        self.inner.display_unsupported_video(url)
+    }
+
+    fn load_device_font(&self, query: &FontQuery, register: &mut dyn FnMut(FontDefinition)) {
+        self.inner.load_device_font(query, register)
+    }
+
+    fn sort_device_fonts(
+        &self,
+        query: &FontQuery,
+        register: &mut dyn FnMut(FontDefinition),
+    ) -> Vec<FontQuery> {
+        self.inner.sort_device_fonts(query, register)
+    }
+
+    /// A text field gained focus.
+    fn open_virtual_keyboard(&self) {
+        ime::set_text_focus(true);
+        self.host.call("onTextInputRequested", "(Z)V", &[JValue::from(true)]);
+    }
+
+    fn close_virtual_keyboard(&self) {
+        ime::set_text_focus(false);
+        self.host.call("onTextInputRequested", "(Z)V", &[JValue::from(false)]);
+    }
+
+    fn language(&self) -> LanguageIdentifier {
+        self.inner.language()
+    }
+
+    /// The host shows its document picker and answers with `nativeFileDialogResult`.
+    fn display_file_open_dialog(&mut self, filters: Vec<FileFilter>) -> Option<DialogResultFuture> {
+        let (id, receiver) = filedialog::request();
+        let extensions = filter_extensions(&filters);
+        let result = self.host.with_env(|env| {
+            let array = env.new_object_array(extensions.len() as i32, "java/lang/String", JObject::null())?;
+            for (index, extension) in extensions.iter().enumerate() {
+                let extension = env.new_string(extension)?;
+                env.set_object_array_element(&array, index as i32, extension)?;
+            }
+            env.call_method(
+                &self.host.thiz,
+                "onFileOpenRequested",
+                "(I[Ljava/lang/String;)V",
+                &[JValue::from(id), JValue::Object(&array)],
+            )
+            .map(|_| ())
+        });
+        if let Err(e) = result {
+            error!("Failed to show file open dialog! {e}");
+            filedialog::answer(id, DialogAnswer::Cancelled);
+            return None;
+        }
+        Some(dialog_result(receiver))
+    }
+
+    fn display_file_save_dialog(&mut self, file_name: String, title: String) -> Option<DialogResultFuture> {
+        let (id, receiver) = filedialog::request();
+        let result = self.host.with_env(|env| {
+            let file_name = env.new_string(&file_name)?;
+            let title = env.new_string(&title)?;
+            env.call_method(
+                &self.host.thiz,
+                "onFileSaveRequested",
+                "(ILjava/lang/String;Ljava/lang/String;)V",
+                &[JValue::from(id), JValue::Object(&file_name), JValue::Object(&title)],
+            )
+            .map(|_| ())
+        });
+        if let Err(e) = result {
+            error!("Failed to show file save dialog! {e}");
+            filedialog::answer(id, DialogAnswer::Cancelled);
+            return None;
+        }
+        Some(dialog_result(receiver))
+    }
+
+    fn close_file_dialog(&mut self) {
+        filedialog::cancel_all();
+        self.inner.close_file_dialog()
+    }
+}
@@ -18,6 +18,13 @@ impl JniUtils{
             .expect("Failed to case JObject as f32")
     }
 
+    pub fn as_bool(env: &mut JNIEnv, val: JObject) -> bool {
+        env.call_method(val, "booleanValue", "()Z", &[])
+            .expect("Failed to case JObject as bool")
+            .z()
+            .expect("Failed to case JObject as bool")
+    }
+
     pub fn as_string(env: &mut JNIEnv, val: JObject) -> String {
 
         let clazz = env.find_class("java/io/File").unwrap();
@@ -44,7 +51,8 @@ impl JniUtils{
 #[derive(Clone, Debug)]
 pub enum TypedValue {
     F(f32),
-    S(String)
+    S(String),
+    B(bool)
 }
 
 pub struct Properties {
@@ -80,6 +88,20 @@ impl Properties {
         }
     }
 
+    pub fn b(&mut self, key: &str, def_value: bool) -> bool {
+        if self.data_opt.is_none() {
+            def_value
+        } else {
+            match self.data_opt
+                .as_ref()
+                .unwrap()
+                .get(key) {
+                Some(TypedValue::B(v)) => *v,
+                _ => def_value,
+            }
+        }
+    }
+
     pub fn s(&mut self, key: &str) -> Option<&String> {
         if self.data_opt.is_none() {
             None